@@ -0,0 +1,583 @@
+use snafu::prelude::*;
+
+/// Swaps the red and blue channels of every pixel in `bytes` in place.
+///
+/// # Panics
+/// - `pixel_size` must be at least 3.
+/// - `bytes.len()` must be a multiple of `pixel_size`.
+pub fn swap_red_blue(bytes: &mut [u8], pixel_size: usize) {
+    assert!(pixel_size >= 3, "`pixel_size` must be at least 3");
+    assert!(
+        bytes.len().is_multiple_of(pixel_size),
+        "`bytes.len()` must be a multiple of `pixel_size`"
+    );
+
+    for pixel in bytes.chunks_exact_mut(pixel_size) {
+        pixel.swap(0, 2);
+    }
+}
+
+/// Returns `true` if every RGBA pixel in `bytes` is black, ignoring alpha.
+pub fn is_black(bytes: &[u8]) -> bool {
+    bytes
+        .chunks(4)
+        .all(|pixel| pixel.iter().take(3).all(|&b| b == 0))
+}
+
+#[derive(Debug, Snafu)]
+#[snafu(module)]
+pub enum StrideError {
+    #[snafu(display("the stride ({stride}) is smaller than a row ({row_len} bytes)"))]
+    TooSmall { stride: usize, row_len: usize },
+
+    #[snafu(display("the buffer ({actual} bytes) is too small for the frame ({expected} bytes)"))]
+    BufferTooShort { expected: usize, actual: usize },
+}
+
+/// Checks that a buffer of `len` bytes holds `rows` rows of `row_len` bytes, where
+/// consecutive rows start `stride` bytes apart. The last row doesn't need padding after it.
+pub fn check_strided(
+    len: usize,
+    stride: usize,
+    row_len: usize,
+    rows: usize,
+) -> Result<(), StrideError> {
+    ensure!(
+        stride >= row_len,
+        stride_error::TooSmallSnafu { stride, row_len }
+    );
+
+    let expected = match rows {
+        0 => 0,
+        rows => (rows - 1).saturating_mul(stride).saturating_add(row_len),
+    };
+
+    ensure!(
+        len >= expected,
+        stride_error::BufferTooShortSnafu {
+            expected,
+            actual: len,
+        }
+    );
+
+    Ok(())
+}
+
+/// Calls `f` with each of the first `rows` rows of `src` and the matching row of `dst`.
+///
+/// Rows are described by `(stride, row_len)`: each is `row_len` bytes long, and consecutive
+/// rows start `stride` bytes apart.
+///
+/// # Panics
+/// - Both strides must be at least the row length.
+/// - Both buffers must be large enough to hold `rows` rows at their stride.
+fn map_rows<F>(
+    src: &[u8],
+    src_rows: (usize, usize),
+    dst: &mut [u8],
+    dst_rows: (usize, usize),
+    rows: usize,
+    mut f: F,
+) where
+    F: FnMut(&[u8], &mut [u8]),
+{
+    let ((src_stride, src_row_len), (dst_stride, dst_row_len)) = (src_rows, dst_rows);
+
+    assert!(
+        src_stride >= src_row_len && dst_stride >= dst_row_len,
+        "strides must be at least the row length"
+    );
+
+    for row in 0..rows {
+        let src_row = &src[row * src_stride..][..src_row_len];
+        let dst_row = &mut dst[row * dst_stride..][..dst_row_len];
+        f(src_row, dst_row);
+    }
+}
+
+/// Copies `rows` rows of `row_len` bytes from `src` to `dst`, where consecutive rows start
+/// `src_stride` and `dst_stride` bytes apart respectively.
+///
+/// # Panics
+/// - Both strides must be at least `row_len`.
+/// - Both buffers must be large enough to hold `rows` rows at their stride.
+pub fn copy_rows(
+    src: &[u8],
+    src_stride: usize,
+    dst: &mut [u8],
+    dst_stride: usize,
+    row_len: usize,
+    rows: usize,
+) {
+    let (src_rows, dst_rows) = ((src_stride, row_len), (dst_stride, row_len));
+    map_rows(src, src_rows, dst, dst_rows, rows, |src, dst| {
+        dst.copy_from_slice(src)
+    });
+}
+
+/// Expands `rows` rows of `width` RGB pixels, starting `src_stride` bytes apart in `src`, to
+/// tightly packed, opaque RGBA rows in `dst`.
+///
+/// # Panics
+/// - `src_stride` must be at least `width * 3`.
+/// - `src` must hold `rows` rows at its stride, and `dst` `rows` RGBA rows.
+pub fn rgb_rows_to_rgba(src: &[u8], src_stride: usize, width: usize, rows: usize, dst: &mut [u8]) {
+    let (src_rows, dst_rows) = ((src_stride, width * 3), (width * 4, width * 4));
+    map_rows(src, src_rows, dst, dst_rows, rows, |src, dst| {
+        expand_to_rgba(src, 3, dst)
+    });
+}
+
+/// Down-converts R10G10B10A2 pixels in `src` to RGBA (8 bits per channel) in `dst`.
+///
+/// Each source pixel is a little-endian `u32` holding red in bits 0-9, green in bits 10-19,
+/// blue in bits 20-29 and alpha in bits 30-31 (i.e. `DXGI_FORMAT_R10G10B10A2_UNORM`).
+///
+/// # Panics
+/// - `src.len()` must be a multiple of 4.
+/// - `dst` must be at least as long as `src`.
+pub fn rgb10a2_to_rgba(src: &[u8], dst: &mut [u8]) {
+    assert!(
+        src.len().is_multiple_of(4),
+        "`src.len()` must be a multiple of 4"
+    );
+    assert!(
+        dst.len() >= src.len(),
+        "`dst` must be at least as long as `src`"
+    );
+
+    for (src, dst) in src.chunks_exact(4).zip(dst.chunks_exact_mut(4)) {
+        let pixel = u32::from_le_bytes([src[0], src[1], src[2], src[3]]);
+        let channel = |shift: u32| ((pixel >> shift) & 0x3ff) >> 2;
+
+        dst[0] = channel(0) as u8;
+        dst[1] = channel(10) as u8;
+        dst[2] = channel(20) as u8;
+        dst[3] = ((pixel >> 30) * 0x55) as u8;
+    }
+}
+
+/// Expands grayscale (`channels == 1`) or RGB (`channels == 3`) pixels in `src` to opaque RGBA
+/// in `dst`.
+///
+/// # Panics
+/// - `channels` must be 1 or 3.
+/// - `src.len()` must be a multiple of `channels`.
+/// - `dst` must hold at least `src.len() / channels` RGBA pixels.
+pub fn expand_to_rgba(src: &[u8], channels: usize, dst: &mut [u8]) {
+    assert!(matches!(channels, 1 | 3), "`channels` must be 1 or 3");
+    assert!(
+        src.len().is_multiple_of(channels),
+        "`src.len()` must be a multiple of `channels`"
+    );
+    assert!(
+        dst.len() / 4 >= src.len() / channels,
+        "`dst` must hold every pixel of `src`"
+    );
+
+    for (src, dst) in src.chunks_exact(channels).zip(dst.chunks_exact_mut(4)) {
+        match *src {
+            [gray] => dst.copy_from_slice(&[gray, gray, gray, u8::MAX]),
+            [r, g, b] => dst.copy_from_slice(&[r, g, b, u8::MAX]),
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Scales the `src_width`×`src_height` RGBA image in `src` to `dst_width`×`dst_height` in
+/// `dst` with bilinear interpolation. Rows are tightly packed in both buffers.
+///
+/// # Panics
+/// - `src` must hold exactly `src_width * src_height` pixels.
+/// - `dst` must hold at least `dst_width * dst_height` pixels.
+pub fn scale_bilinear(
+    src: &[u8],
+    src_width: usize,
+    src_height: usize,
+    dst: &mut [u8],
+    dst_width: usize,
+    dst_height: usize,
+) {
+    assert_eq!(
+        src.len(),
+        src_width * src_height * 4,
+        "`src` must hold `src_width * src_height` pixels"
+    );
+    assert!(
+        dst.len() >= dst_width * dst_height * 4,
+        "`dst` must hold `dst_width * dst_height` pixels"
+    );
+
+    if src.is_empty() {
+        return;
+    }
+
+    // Maps a destination coordinate to the two nearest source ones and the weight of the
+    // second, sampling at pixel centers.
+    let sample = |dst: usize, dst_len: usize, src_len: usize| {
+        let pos = ((dst as f32 + 0.5) * src_len as f32 / dst_len as f32 - 0.5).max(0.0);
+        let lo = (pos as usize).min(src_len - 1);
+        let hi = (lo + 1).min(src_len - 1);
+        (lo, hi, pos - lo as f32)
+    };
+
+    let pixel = |x: usize, y: usize| &src[(y * src_width + x) * 4..][..4];
+
+    for y in 0..dst_height {
+        let (y0, y1, fy) = sample(y, dst_height, src_height);
+
+        for x in 0..dst_width {
+            let (x0, x1, fx) = sample(x, dst_width, src_width);
+            let out = &mut dst[(y * dst_width + x) * 4..][..4];
+
+            for (c, out) in out.iter_mut().enumerate() {
+                let lerp = |a: u8, b: u8, t: f32| a as f32 + (b as f32 - a as f32) * t;
+                let top = lerp(pixel(x0, y0)[c], pixel(x1, y0)[c], fx);
+                let bottom = lerp(pixel(x0, y1)[c], pixel(x1, y1)[c], fx);
+                *out = (top + (bottom - top) * fy).round() as u8;
+            }
+        }
+    }
+}
+
+/// Like [`scale_bilinear`], but picks the nearest source pixel instead of interpolating.
+pub fn scale_nearest(
+    src: &[u8],
+    src_width: usize,
+    src_height: usize,
+    dst: &mut [u8],
+    dst_width: usize,
+    dst_height: usize,
+) {
+    assert_eq!(
+        src.len(),
+        src_width * src_height * 4,
+        "`src` must hold `src_width * src_height` pixels"
+    );
+    assert!(
+        dst.len() >= dst_width * dst_height * 4,
+        "`dst` must hold `dst_width * dst_height` pixels"
+    );
+
+    for y in 0..dst_height {
+        let src_y = y * src_height / dst_height;
+
+        for x in 0..dst_width {
+            let src_x = x * src_width / dst_width;
+            let src = &src[(src_y * src_width + src_x) * 4..][..4];
+            dst[(y * dst_width + x) * 4..][..4].copy_from_slice(src);
+        }
+    }
+}
+
+/// Copies BGR (`pixel_size == 3`) or BGRA (`pixel_size == 4`) pixels from `src` to `dst` as
+/// RGBA, swapping the red and blue channels. BGR pixels become opaque.
+///
+/// # Panics
+/// - `pixel_size` must be 3 or 4.
+/// - `src.len()` must be a multiple of `pixel_size`.
+/// - `dst` must hold at least `src.len() / pixel_size` RGBA pixels.
+pub fn bgr_to_rgba(src: &[u8], pixel_size: usize, dst: &mut [u8]) {
+    assert!(matches!(pixel_size, 3 | 4), "`pixel_size` must be 3 or 4");
+    assert!(
+        src.len().is_multiple_of(pixel_size),
+        "`src.len()` must be a multiple of `pixel_size`"
+    );
+    assert!(
+        dst.len() / 4 >= src.len() / pixel_size,
+        "`dst` must hold every pixel of `src`"
+    );
+
+    for (src, dst) in src.chunks_exact(pixel_size).zip(dst.chunks_exact_mut(4)) {
+        let alpha = src.get(3).copied().unwrap_or(u8::MAX);
+        dst.copy_from_slice(&[src[2], src[1], src[0], alpha]);
+    }
+}
+
+/// Copies 16-bit values (e.g. half floats) from `src` to `dst` as native-endian bytes.
+///
+/// # Panics
+/// - `dst` must hold at least `src.len() * 2` bytes.
+pub fn copy_u16(src: &[u16], dst: &mut [u8]) {
+    assert!(
+        dst.len() / 2 >= src.len(),
+        "`dst` must hold every value of `src`"
+    );
+
+    for (src, dst) in src.iter().zip(dst.chunks_exact_mut(2)) {
+        dst.copy_from_slice(&src.to_ne_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expand_to_rgba_expands_grayscale() {
+        let mut dst = [0; 8];
+        expand_to_rgba(&[0x10, 0xf0], 1, &mut dst);
+        assert_eq!(dst, [0x10, 0x10, 0x10, 0xff, 0xf0, 0xf0, 0xf0, 0xff]);
+    }
+
+    #[test]
+    fn expand_to_rgba_expands_rgb() {
+        let mut dst = [0; 8];
+        expand_to_rgba(&[1, 2, 3, 4, 5, 6], 3, &mut dst);
+        assert_eq!(dst, [1, 2, 3, 0xff, 4, 5, 6, 0xff]);
+    }
+
+    #[test]
+    fn expand_to_rgba_leaves_the_rest_of_dst_alone() {
+        let mut dst = [0xaa; 8];
+        expand_to_rgba(&[1, 2, 3], 3, &mut dst);
+        assert_eq!(dst, [1, 2, 3, 0xff, 0xaa, 0xaa, 0xaa, 0xaa]);
+    }
+
+    #[test]
+    #[should_panic(expected = "`channels` must be 1 or 3")]
+    fn expand_to_rgba_rejects_other_channel_counts() {
+        expand_to_rgba(&[0; 4], 4, &mut [0; 4]);
+    }
+
+    #[test]
+    fn swap_red_blue_swaps_the_first_and_third_bytes() {
+        let mut rgb = [1, 2, 3, 4, 5, 6];
+        swap_red_blue(&mut rgb, 3);
+        assert_eq!(rgb, [3, 2, 1, 6, 5, 4]);
+
+        let mut rgba = [1, 2, 3, 4, 5, 6, 7, 8];
+        swap_red_blue(&mut rgba, 4);
+        assert_eq!(rgba, [3, 2, 1, 4, 7, 6, 5, 8]);
+    }
+
+    #[test]
+    #[should_panic(expected = "`bytes.len()` must be a multiple of `pixel_size`")]
+    fn swap_red_blue_rejects_partial_pixels() {
+        swap_red_blue(&mut [0; 5], 4);
+    }
+
+    #[test]
+    fn bgr_to_rgba_swaps_channels_and_keeps_alpha() {
+        let mut dst = [0; 8];
+        bgr_to_rgba(
+            &[0x10, 0x20, 0x30, 0x40, 0x50, 0x60, 0x70, 0x80],
+            4,
+            &mut dst,
+        );
+        assert_eq!(dst, [0x30, 0x20, 0x10, 0x40, 0x70, 0x60, 0x50, 0x80]);
+    }
+
+    #[test]
+    fn bgr_to_rgba_makes_bgr_pixels_opaque() {
+        let mut dst = [0; 8];
+        bgr_to_rgba(&[1, 2, 3, 4, 5, 6], 3, &mut dst);
+        assert_eq!(dst, [3, 2, 1, 0xff, 6, 5, 4, 0xff]);
+    }
+
+    #[test]
+    #[should_panic(expected = "`pixel_size` must be 3 or 4")]
+    fn bgr_to_rgba_rejects_other_pixel_sizes() {
+        bgr_to_rgba(&[0; 2], 2, &mut [0; 4]);
+    }
+
+    /// Packs 10-bit `r`, `g`, `b` and 2-bit `a` into an R10G10B10A2 pixel's bytes.
+    fn rgb10a2(r: u32, g: u32, b: u32, a: u32) -> [u8; 4] {
+        (r | g << 10 | b << 20 | a << 30).to_le_bytes()
+    }
+
+    #[test]
+    fn rgb10a2_to_rgba_keeps_full_scale_channels() {
+        let mut dst = [0; 4];
+        rgb10a2_to_rgba(&rgb10a2(0x3ff, 0x3ff, 0x3ff, 3), &mut dst);
+        assert_eq!(dst, [0xff; 4]);
+
+        rgb10a2_to_rgba(&rgb10a2(0, 0, 0, 0), &mut dst);
+        assert_eq!(dst, [0; 4]);
+    }
+
+    #[test]
+    fn rgb10a2_to_rgba_spreads_alpha_over_the_full_range() {
+        for (alpha, expected) in [(0, 0x00), (1, 0x55), (2, 0xaa), (3, 0xff)] {
+            let mut dst = [0; 4];
+            rgb10a2_to_rgba(&rgb10a2(0, 0, 0, alpha), &mut dst);
+            assert_eq!(dst[3], expected);
+        }
+    }
+
+    #[test]
+    fn rgb10a2_to_rgba_reads_red_from_the_low_bits() {
+        // Distinct values, keeping the top 8 of each channel's 10 bits.
+        let src: Vec<u8> = [
+            rgb10a2(0x3ff, 0, 0, 0),
+            rgb10a2(0x100 << 1, 0x3fc, 0x004, 1),
+        ]
+        .concat();
+        let mut dst = [0; 8];
+
+        rgb10a2_to_rgba(&src, &mut dst);
+        assert_eq!(dst, [0xff, 0, 0, 0, 0x80, 0xff, 0x01, 0x55]);
+    }
+
+    /// Returns an image of gray pixels with the given values, in row order.
+    fn gray(values: &[u8]) -> Vec<u8> {
+        values.iter().flat_map(|&v| [v, v, v, v]).collect()
+    }
+
+    #[test]
+    fn scaling_to_the_same_size_copies_the_image() {
+        let src: Vec<u8> = (0..3 * 2 * 4).collect();
+
+        for scale in [scale_bilinear, scale_nearest] {
+            let mut dst = vec![0; src.len()];
+            scale(&src, 3, 2, &mut dst, 3, 2);
+            assert_eq!(dst, src);
+        }
+    }
+
+    #[test]
+    fn bilinear_downscaling_by_two_averages_blocks() {
+        let src = gray(&[0, 100, 200, 40, 20, 120, 0, 60]);
+        let mut dst = vec![0; 2 * 4];
+
+        scale_bilinear(&src, 4, 2, &mut dst, 2, 1);
+        assert_eq!(dst, gray(&[60, 75]));
+    }
+
+    #[test]
+    fn nearest_downscaling_by_two_picks_the_top_left_of_blocks() {
+        let src = gray(&[0, 100, 200, 40, 20, 120, 0, 60]);
+        let mut dst = vec![0; 2 * 4];
+
+        scale_nearest(&src, 4, 2, &mut dst, 2, 1);
+        assert_eq!(dst, gray(&[0, 200]));
+    }
+
+    #[test]
+    fn bilinear_upscaling_interpolates_between_pixel_centers() {
+        let mut dst = vec![0; 4 * 4];
+
+        scale_bilinear(&gray(&[0, 200]), 2, 1, &mut dst, 4, 1);
+        assert_eq!(dst, gray(&[0, 50, 150, 200]));
+    }
+
+    #[test]
+    fn nearest_upscaling_repeats_pixels() {
+        let mut dst = vec![0; 4 * 2 * 4];
+
+        scale_nearest(&gray(&[10, 20]), 2, 1, &mut dst, 4, 2);
+        assert_eq!(dst, gray(&[10, 10, 20, 20, 10, 10, 20, 20]));
+    }
+
+    /// Times bilinear scaling at common downscale ratios. Meaningful in release builds only:
+    /// `cargo test --release -- --ignored --nocapture scale_bilinear_fits`.
+    #[test]
+    #[ignore = "a benchmark"]
+    fn scale_bilinear_fits_in_a_frame_budget() {
+        use std::time::{Duration, Instant};
+
+        // One frame at 30 fps.
+        const BUDGET: Duration = Duration::from_millis(33);
+        const RUNS: u32 = 10;
+
+        let ratios = [
+            ((3840, 2160), (1920, 1080)),
+            ((3840, 2160), (1280, 720)),
+            ((1920, 1080), (1280, 720)),
+            ((1920, 1080), (640, 360)),
+        ];
+
+        for ((src_width, src_height), (dst_width, dst_height)) in ratios {
+            let src: Vec<u8> = (0..src_width * src_height * 4).map(|i| i as u8).collect();
+            let mut dst = vec![0; dst_width * dst_height * 4];
+
+            let start = Instant::now();
+
+            for _ in 0..RUNS {
+                scale_bilinear(&src, src_width, src_height, &mut dst, dst_width, dst_height);
+            }
+
+            let per_frame = start.elapsed() / RUNS;
+            println!("{src_width}x{src_height} -> {dst_width}x{dst_height}: {per_frame:?}");
+
+            if !cfg!(debug_assertions) {
+                assert!(per_frame < BUDGET, "{per_frame:?} exceeds {BUDGET:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn check_strided_accepts_packed_and_padded_rows() {
+        assert!(check_strided(36, 12, 12, 3).is_ok());
+        assert!(check_strided(44, 16, 12, 3).is_ok());
+        assert!(check_strided(0, 16, 12, 0).is_ok());
+    }
+
+    #[test]
+    fn check_strided_does_not_need_padding_after_the_last_row() {
+        assert!(check_strided(2 * 16 + 12, 16, 12, 3).is_ok());
+        assert!(matches!(
+            check_strided(2 * 16 + 11, 16, 12, 3),
+            Err(StrideError::BufferTooShort {
+                expected: 44,
+                actual: 43,
+            })
+        ));
+    }
+
+    #[test]
+    fn check_strided_rejects_strides_shorter_than_a_row() {
+        assert!(matches!(
+            check_strided(100, 11, 12, 3),
+            Err(StrideError::TooSmall {
+                stride: 11,
+                row_len: 12,
+            })
+        ));
+    }
+
+    #[test]
+    fn check_strided_saturates_instead_of_overflowing() {
+        assert!(matches!(
+            check_strided(100, usize::MAX, 12, 3),
+            Err(StrideError::BufferTooShort {
+                expected: usize::MAX,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn copy_rows_drops_source_padding() {
+        // Three rows of three bytes, padded to a stride of four.
+        let src = [1, 2, 3, 0, 4, 5, 6, 0, 7, 8, 9];
+        let mut dst = [0; 9];
+
+        copy_rows(&src, 4, &mut dst, 3, 3, 3);
+        assert_eq!(dst, [1, 2, 3, 4, 5, 6, 7, 8, 9]);
+    }
+
+    #[test]
+    fn copy_rows_skips_destination_padding() {
+        let src = [1, 2, 3, 4, 5, 6];
+        let mut dst = [0xaa; 10];
+
+        copy_rows(&src, 3, &mut dst, 5, 3, 2);
+        assert_eq!(dst, [1, 2, 3, 0xaa, 0xaa, 4, 5, 6, 0xaa, 0xaa]);
+    }
+
+    #[test]
+    fn rgb_rows_to_rgba_expands_odd_widths_at_a_padded_stride() {
+        // Two rows of three RGB pixels (9 bytes), padded to a 4-byte aligned stride of 12.
+        let src: Vec<u8> = (1..=9).chain([0; 3]).chain(11..=19).collect();
+        let mut dst = [0; 2 * 3 * 4];
+
+        rgb_rows_to_rgba(&src, 12, 3, 2, &mut dst);
+
+        assert_eq!(dst[..12], [1, 2, 3, 0xff, 4, 5, 6, 0xff, 7, 8, 9, 0xff]);
+        assert_eq!(
+            dst[12..],
+            [11, 12, 13, 0xff, 14, 15, 16, 0xff, 17, 18, 19, 0xff]
+        );
+    }
+}
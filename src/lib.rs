@@ -1,66 +1,2678 @@
 #![cfg(windows)]
 
+mod convert;
+mod receiver;
 mod sender;
 mod utils;
 mod win32;
 
-use crate::sender::{FrameConfig, Sender};
+use crate::sender::{
+    DeviceNames, FrameConfig, FrameValidator, SendStats, Sender, SenderOptions, StateListener,
+};
+use crate::win32::{Event, WaitEventError};
+use napi::Env;
+use napi::bindgen_prelude::{
+    ArrayBuffer, AsyncTask, BigInt, Buffer, Function, Task, This, Uint8Array, Uint16Array, Unknown,
+};
+use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
 use napi_derive::napi;
 use snafu::Report;
+use std::num::{NonZeroU8, NonZeroU32};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
 #[napi]
 pub const MAX_WIDTH: u32 = sender::MAX_WIDTH;
 
 #[napi]
 pub const MAX_HEIGHT: u32 = sender::MAX_HEIGHT;
 
+/// The number of devices UnityCapture's installer registers at most. Device indices must be
+/// less than this.
+#[napi]
+pub const MAX_DEVICES: u32 = sender::MAX_DEVICES;
+
+/// The size of the image region in bytes, which bounds every frame regardless of its format.
+#[napi]
+pub const MAX_IMAGE_SIZE: u32 = sender::MAX_IMAGE_SIZE as u32;
+
+/// Describes the shared memory protocol this build targets.
+#[napi(object)]
+pub struct ProtocolInfo {
+    /// The size of the image region in bytes.
+    pub max_image_size: u32,
+    /// The size of the frame header in bytes.
+    pub header_size: u32,
+    /// The common prefix of all the shared object names.
+    pub object_name_base: String,
+}
+
+/// The connection state of a running camera.
+///
+/// A camera connects on the first `send` after `start`, retrying on every send until it
+/// succeeds, and disconnects when a send fails in a way that suggests the driver went away.
+#[napi(string_enum = "lowercase")]
+pub enum ConnectionState {
+    Disconnected,
+    Connecting,
+    Connected,
+}
+
+impl From<sender::ConnectionState> for ConnectionState {
+    fn from(state: sender::ConnectionState) -> Self {
+        match state {
+            sender::ConnectionState::Disconnected => ConnectionState::Disconnected,
+            sender::ConnectionState::Connecting => ConnectionState::Connecting,
+            sender::ConnectionState::Connected => ConnectionState::Connected,
+        }
+    }
+}
+
+type StateChangeCallback =
+    ThreadsafeFunction<ConnectionState, (), ConnectionState, napi::Status, false, true>;
+
+type WarningCallback = ThreadsafeFunction<String, (), String, napi::Status, false, true>;
+
+type FrameRequestCallback = ThreadsafeFunction<(), (), (), napi::Status, true, true>;
+
+/// The pixel format of a frame. Every format is RGBA.
+#[napi(string_enum = "camelCase")]
+pub enum PixelFormat {
+    /// 8 bits per channel, 4 bytes per pixel. The default.
+    Uint8,
+    /// 16-bit floats per channel (8 bytes per pixel), gamma-encoded.
+    Fp16Gamma,
+    /// 16-bit floats per channel (8 bytes per pixel), linear.
+    Fp16Linear,
+}
+
+impl From<PixelFormat> for sender::PixelFormat {
+    fn from(format: PixelFormat) -> Self {
+        match format {
+            PixelFormat::Uint8 => sender::PixelFormat::Uint8,
+            PixelFormat::Fp16Gamma => sender::PixelFormat::Fp16Gamma,
+            PixelFormat::Fp16Linear => sender::PixelFormat::Fp16Linear,
+        }
+    }
+}
+
+/// Whether the receiver resizes frames that don't match the resolution it negotiated.
+#[napi(string_enum = "lowercase")]
+pub enum ResizeMode {
+    /// Frames are shown at their own size.
+    Disabled,
+    /// Frames are scaled with linear interpolation. The default.
+    Linear,
+}
+
+impl From<ResizeMode> for sender::ResizeMode {
+    fn from(mode: ResizeMode) -> Self {
+        match mode {
+            ResizeMode::Disabled => sender::ResizeMode::Disabled,
+            ResizeMode::Linear => sender::ResizeMode::Linear,
+        }
+    }
+}
+
+/// The kernel object namespace the driver's shared objects are looked up in.
+#[napi(string_enum = "lowercase")]
+pub enum ObjectNamespace {
+    /// The namespace of the current session. The default.
+    Session,
+    /// The global namespace (`Global\`), e.g. for a driver used by a service in session 0.
+    Global,
+    /// The session namespace, spelled out (`Local\`).
+    Local,
+}
+
+impl ObjectNamespace {
+    fn prefix(&self) -> &'static str {
+        match self {
+            ObjectNamespace::Session => "",
+            ObjectNamespace::Global => "Global\\",
+            ObjectNamespace::Local => "Local\\",
+        }
+    }
+}
+
+/// Whether the receiver mirrors the image.
+#[napi(string_enum = "lowercase")]
+pub enum MirrorMode {
+    /// The default.
+    Disabled,
+    Horizontal,
+}
+
+impl From<MirrorMode> for sender::MirrorMode {
+    fn from(mode: MirrorMode) -> Self {
+        match mode {
+            MirrorMode::Disabled => sender::MirrorMode::Disabled,
+            MirrorMode::Horizontal => sender::MirrorMode::Horizontal,
+        }
+    }
+}
+
+/// How the `SENT` event is signaled after each frame.
+#[napi(string_enum = "camelCase")]
+pub enum SentSignal {
+    /// `SetEvent`, the default. Always wakes the driver.
+    Set,
+    /// `PulseEvent`. Only wakes a consumer that is waiting at that moment.
+    Pulse,
+    /// `SetEvent` immediately followed by `ResetEvent`. Only wakes a consumer that is waiting
+    /// at that moment.
+    SetThenReset,
+}
+
+impl From<SentSignal> for sender::SentSignal {
+    fn from(signal: SentSignal) -> Self {
+        match signal {
+            SentSignal::Set => sender::SentSignal::Set,
+            SentSignal::Pulse => sender::SentSignal::Pulse,
+            SentSignal::SetThenReset => sender::SentSignal::SetThenReset,
+        }
+    }
+}
+
+/// What `trySendNow` did with a frame.
+#[napi(string_enum = "lowercase")]
+#[derive(Default)]
+pub enum SendOutcome {
+    /// The frame was published.
+    Sent,
+    /// The consumer wasn't asking for a frame. Also reported when sending failed and the
+    /// failure was retried silently (see `RetryPolicy`).
+    #[default]
+    Skipped,
+    /// The frame validator rejected the frame.
+    Rejected,
+    /// The frame came sooner than `setMaxFps` allows.
+    Throttled,
+}
+
+impl From<sender::SendOutcome> for SendOutcome {
+    fn from(outcome: sender::SendOutcome) -> Self {
+        match outcome {
+            sender::SendOutcome::Sent => SendOutcome::Sent,
+            sender::SendOutcome::Skipped => SendOutcome::Skipped,
+            sender::SendOutcome::Rejected => SendOutcome::Rejected,
+            sender::SendOutcome::Throttled => SendOutcome::Throttled,
+        }
+    }
+}
+
+/// Decides whether a failed send is silently retried on the next call (`true`) or
+/// reported to JS (`false`).
+type RetryClassifier = Arc<dyn Fn(&sender::Error) -> bool + Send + Sync>;
+
+/// Which send failures are silently retried rather than thrown.
+#[napi(string_enum = "lowercase")]
+pub enum RetryPolicy {
+    /// Connection failures are retried, unless connecting can't succeed by itself (e.g. access
+    /// to the driver's objects is denied); failures while sending are thrown. A driver that
+    /// isn't installed is retried too (`INIT_NO_RECEIVER`), since it looks the same as one no
+    /// application is using yet; see `isDriverAvailable` and `troubleshoot`.
+    Default,
+    /// Every failure is thrown.
+    Never,
+    /// Every failure is retried (i.e. ignored).
+    Always,
+}
+
+impl RetryPolicy {
+    fn classifier(&self) -> RetryClassifier {
+        match self {
+            RetryPolicy::Default => Arc::new(sender::Error::should_retry),
+            RetryPolicy::Never => Arc::new(|_| false),
+            RetryPolicy::Always => Arc::new(|_| true),
+        }
+    }
+}
+
+/// Counters describing what happened to the frames passed to `send`.
+///
+/// Publishing a frame consists of waiting for the consumer to ask for it (see
+/// `setWantTimeout`) and copying it under the lock, which are timed separately.
+#[napi(object)]
+pub struct Stats {
+    /// Frames published.
+    pub published: i64,
+    /// Frames skipped by the frame validator.
+    pub rejected: i64,
+    /// Frames skipped because they came sooner than `setMaxFps` allows.
+    pub throttled: i64,
+    /// The longest time the shared memory lock was held for a single frame, in microseconds.
+    pub max_critical_section_us: i64,
+    /// The total time the shared memory lock was held, i.e. spent copying frames, in
+    /// microseconds. Divide by `published` for the average.
+    pub total_critical_section_us: i64,
+    /// The total time spent waiting for the consumer to ask for frames, in microseconds.
+    pub total_want_wait_us: i64,
+    /// Frames that held the lock longer than the critical section budget.
+    pub over_budget: i64,
+    /// Frames published after waiting for the consumer to ask for them timed out.
+    pub want_timeouts: i64,
+    /// Frames given up on because the lock couldn't be taken in time (see `setLockTimeout`).
+    pub lock_timeouts: i64,
+    /// Failed attempts to connect to the driver.
+    pub init_failures: i64,
+}
+
+impl From<SendStats> for Stats {
+    fn from(stats: SendStats) -> Self {
+        Stats {
+            published: stats.published as i64,
+            rejected: stats.rejected as i64,
+            throttled: stats.throttled as i64,
+            max_critical_section_us: stats.max_critical_section.as_micros() as i64,
+            total_critical_section_us: stats.total_critical_section.as_micros() as i64,
+            total_want_wait_us: stats.total_want_wait.as_micros() as i64,
+            over_budget: stats.over_budget as i64,
+            want_timeouts: stats.want_timeouts as i64,
+            lock_timeouts: stats.lock_timeouts as i64,
+            init_failures: stats.init_failures as i64,
+        }
+    }
+}
+
+/// Settings overriding the camera's configuration for a single `sendWithOptions` call.
+/// Missing fields keep the configured value.
+#[napi(object)]
+pub struct SendOptions {
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub format: Option<PixelFormat>,
+    pub mirror: Option<MirrorMode>,
+    pub resize: Option<ResizeMode>,
+}
+
+/// Settings changed by `Camera.reconfigure`. Missing fields keep the current value.
+#[napi(object)]
+#[derive(Default)]
+pub struct ReconfigureOptions {
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub format: Option<PixelFormat>,
+    pub mirror: Option<MirrorMode>,
+    pub resize: Option<ResizeMode>,
+    /// See `Camera.setDevice`. `base` and `namespace` can only be given along with
+    /// `deviceIndex`.
+    pub device_index: Option<u32>,
+    pub base: Option<String>,
+    pub namespace: Option<ObjectNamespace>,
+}
+
+/// The result of `Camera.sendAndConfirm`.
+#[napi(object)]
+#[derive(Default)]
+pub struct SendConfirmation {
+    /// Whether the frame was published (rather than rejected or retried later).
+    pub published: bool,
+    /// Whether the consumer asked for the next frame in time.
+    pub confirmed: bool,
+    /// How long was spent waiting for the consumer, in microseconds.
+    pub waited_micros: i64,
+}
+
+impl From<sender::Confirmation> for SendConfirmation {
+    fn from(confirmation: sender::Confirmation) -> Self {
+        SendConfirmation {
+            published: confirmation.published,
+            confirmed: confirmation.confirmed,
+            waited_micros: confirmation.waited.as_micros() as i64,
+        }
+    }
+}
+
+/// The result of `Camera.troubleshoot`.
+#[napi(object)]
+pub struct TroubleshootReport {
+    /// Whether the driver's shared objects exist, or `null` if checking failed.
+    pub driver_available: Option<bool>,
+    /// Whether `start` has been called (and `stop` hasn't since).
+    pub running: bool,
+    pub connection_state: ConnectionState,
+    pub stats: Stats,
+    /// Human-readable findings, most fundamental first. Empty if nothing looks wrong.
+    pub findings: Vec<String>,
+}
+
+/// The result of `Camera.snapshot`: the header fields as the driver would read them, and the
+/// frame they describe.
+#[napi(object)]
+pub struct FrameSnapshot {
+    pub max_size: u32,
+    pub width: i32,
+    pub height: i32,
+    /// In pixels, not bytes.
+    pub stride: i32,
+    pub format: i32,
+    pub resize_mode: i32,
+    pub mirror_mode: i32,
+    pub timeout_ms: i32,
+    /// Why the header doesn't describe a frame a camera could have sent, or `null` if it does.
+    pub invalid_header: Option<String>,
+    /// The frame, or empty if the header is invalid.
+    pub image: Buffer,
+}
+
+impl From<sender::Snapshot> for FrameSnapshot {
+    fn from(snapshot: sender::Snapshot) -> Self {
+        let header = snapshot.header;
+
+        FrameSnapshot {
+            max_size: header.max_size,
+            width: header.width,
+            height: header.height,
+            stride: header.stride,
+            format: header.format,
+            resize_mode: header.resize_mode,
+            mirror_mode: header.mirror_mode,
+            timeout_ms: header.timeout,
+            invalid_header: snapshot
+                .config
+                .err()
+                .map(|e| Report::from_error(e).to_string()),
+            image: snapshot.image.into(),
+        }
+    }
+}
+
 #[napi]
 pub struct Camera {
-    sender: Option<Sender>,
+    sender: Option<SharedSender>,
     config: FrameConfig,
+    options: SenderOptions,
+    reject_black_frames: bool,
+    ring: Option<Ring>,
+    completion_event: Option<(String, Event)>,
+    on_state_change: Option<Arc<StateChangeCallback>>,
+    on_warning: Option<WarningCallback>,
+    frame_requests: Option<FrameRequestWatcher>,
+    retry: RetryClassifier,
+    lenient_resize: bool,
+    keep_warm: bool,
+    // A sender stopped while `keep_warm` was set, resumed by the next `start`.
+    warm: Option<SharedSender>,
 }
 
 #[napi]
 impl Camera {
+    /// Creates a camera sending to the device with index `deviceIndex` (0, the first one, by
+    /// default; see `setDevice`). Throws if either dimension is zero or exceeds the maximum, or
+    /// if the index is out of range.
     #[napi(constructor)]
-    pub fn new(width: u32, height: u32) -> Self {
-        Self {
+    pub fn new(width: u32, height: u32, device_index: Option<u32>) -> Result<Self, napi::Error> {
+        let options = SenderOptions {
+            device: device_names(device_index.unwrap_or(0), None, None)?,
+            ..SenderOptions::default()
+        };
+
+        let config = FrameConfig::new(width, height, sender::PixelFormat::Uint8).map_err(|e| {
+            napi::Error::new(napi::Status::InvalidArg, Report::from_error(e).to_string())
+        })?;
+
+        Ok(Self {
             sender: None,
-            config: FrameConfig::new(width, height),
+            config,
+            options,
+            reject_black_frames: false,
+            ring: None,
+            completion_event: None,
+            on_state_change: None,
+            on_warning: None,
+            frame_requests: None,
+            retry: RetryPolicy::Default.classifier(),
+            lenient_resize: false,
+            keep_warm: false,
+            warm: None,
+        })
+    }
+
+    #[napi]
+    pub fn protocol_info() -> ProtocolInfo {
+        ProtocolInfo {
+            max_image_size: sender::MAX_IMAGE_SIZE as u32,
+            header_size: sender::HEADER_SIZE as u32,
+            object_name_base: sender::OBJECT_NAME_BASE.to_owned(),
         }
     }
 
+    /// Returns the size in bytes of the largest frame of `format` that fits in the image
+    /// region, i.e. `MAX_IMAGE_SIZE` rounded down to whole pixels.
     #[napi]
-    pub fn resize(&mut self, width: u32, height: u32) {
-        self.config = FrameConfig::new(width, height);
+    pub fn max_frame_bytes(format: PixelFormat) -> u32 {
+        let bytes_per_pixel = sender::PixelFormat::from(format).bytes_per_pixel();
+        (sender::MAX_IMAGE_SIZE / bytes_per_pixel * bytes_per_pixel) as u32
     }
 
+    /// Checks whether the driver's shared objects exist, without connecting to them.
+    ///
+    /// `device` is the index of the device to check (see `setDevice`), 0 by default.
     #[napi]
-    pub fn start(&mut self) {
-        self.sender = Some(Sender::new());
+    pub fn is_driver_available(device: Option<u32>) -> Result<bool, napi::Error> {
+        let names = device_names(device.unwrap_or(0), None, None)?;
+
+        sender::is_driver_available(&names).map_err(|e| {
+            napi::Error::new(
+                napi::Status::GenericFailure,
+                Report::from_error(e).to_string(),
+            )
+        })
     }
 
+    /// Returns the indices of the devices whose driver objects exist (see
+    /// `isDriverAvailable`), checking the first `count` devices (10 by default).
     #[napi]
-    pub fn stop(&mut self) {
-        self.sender = None;
+    pub fn list_devices(count: Option<u32>) -> Result<Vec<u32>, napi::Error> {
+        let count = count.unwrap_or(sender::MAX_DEVICES);
+
+        sender::available_devices(sender::OBJECT_NAME_BASE, count).map_err(|e| {
+            napi::Error::new(
+                napi::Status::GenericFailure,
+                Report::from_error(e).to_string(),
+            )
+        })
     }
 
+    /// Converts RGB(A) pixels to BGR(A) (or vice versa) in place.
+    ///
+    /// `bytes_per_pixel` must be 3 (24-bit) or 4 (32-bit). The buffer is mutated directly,
+    /// no copy is made.
     #[napi]
-    pub fn send(&mut self, frame: &[u8]) -> Result<(), napi::Error> {
-        let sender = self.sender.as_mut().ok_or_else(|| {
-            napi::Error::new(napi::Status::GenericFailure, "the camera isn't running")
-        })?;
+    pub fn convert_rgb_to_bgr(
+        mut buffer: Uint8Array,
+        bytes_per_pixel: u32,
+    ) -> Result<(), napi::Error> {
+        if !matches!(bytes_per_pixel, 3 | 4) {
+            return Err(napi::Error::new(
+                napi::Status::InvalidArg,
+                format!("`bytesPerPixel` must be 3 or 4, got {bytes_per_pixel}"),
+            ));
+        }
+
+        let pixel_size = bytes_per_pixel as usize;
+
+        if !buffer.len().is_multiple_of(pixel_size) {
+            return Err(napi::Error::new(
+                napi::Status::InvalidArg,
+                format!(
+                    "the buffer length ({}) isn't a multiple of the pixel size ({pixel_size})",
+                    buffer.len()
+                ),
+            ));
+        }
+
+        // SAFETY: JS can't touch the buffer while this (synchronous) call is running.
+        let bytes = unsafe { buffer.as_mut() };
+        convert::swap_red_blue(bytes, pixel_size);
+        Ok(())
+    }
+
+    /// Changes the frame size.
+    ///
+    /// Throws if a dimension is zero or exceeds the maximum, unless lenient resizing is enabled
+    /// (see `setLenientResize`), in which case the current size is kept and a warning is passed
+    /// to the `onWarning` callback.
+    #[napi]
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<(), napi::Error> {
+        match Self::resized_config(self.config, width, height) {
+            Ok(config) => self.config = config,
+            Err(e) if self.lenient_resize => {
+                self.warn(format!(
+                    "{}; keeping {}x{}",
+                    e.reason,
+                    self.config.width(),
+                    self.config.height()
+                ));
+            }
+            Err(e) => return Err(e),
+        }
+
+        Ok(())
+    }
+
+    /// Sets the pixel format of the frames passed to `send`.
+    #[napi]
+    pub fn set_format(&mut self, format: PixelFormat) {
+        self.config = self.config.with_format(format.into());
+    }
+
+    /// Sets a pixel format UnityCapture doesn't define, for custom receivers: `code` is
+    /// written to the header's format field as is, and frames passed to `send` must have
+    /// `bytesPerPixel` bytes per pixel. `sendRaw` doesn't accept frames of such formats.
+    #[napi]
+    pub fn set_custom_format(
+        &mut self,
+        code: i32,
+        bytes_per_pixel: u32,
+    ) -> Result<(), napi::Error> {
+        let Some(bytes_per_pixel) = u8::try_from(bytes_per_pixel).ok().and_then(NonZeroU8::new)
+        else {
+            return Err(napi::Error::new(
+                napi::Status::InvalidArg,
+                format!("`bytesPerPixel` must be between 1 and 255, got {bytes_per_pixel}"),
+            ));
+        };
+
+        self.config = self.config.with_format(sender::PixelFormat::Custom {
+            code,
+            bytes_per_pixel,
+        });
+
+        Ok(())
+    }
+
+    /// Sets how long, in milliseconds, the receiver keeps showing the last frame when no new
+    /// one arrives. `null` restores the default, which is effectively forever.
+    #[napi]
+    pub fn set_frame_timeout(&mut self, timeout_ms: Option<u32>) -> Result<(), napi::Error> {
+        let timeout_ms = timeout_ms.unwrap_or(sender::DEFAULT_FRAME_TIMEOUT_MS);
+
+        self.config = self
+            .config
+            .with_frame_timeout_ms(timeout_ms)
+            .ok_or_else(|| {
+                napi::Error::new(
+                    napi::Status::InvalidArg,
+                    format!(
+                        "the frame timeout ({timeout_ms} ms) must not exceed {}",
+                        i32::MAX
+                    ),
+                )
+            })?;
+
+        Ok(())
+    }
+
+    /// Limits how many frames are published per second. Frames sent sooner than that after the
+    /// previous one are skipped (without an error) and counted in `stats().throttled`. `null`
+    /// or 0 removes the limit, which is the default.
+    #[napi]
+    pub fn set_max_fps(&mut self, max_fps: Option<u32>) {
+        self.config = self.config.with_max_fps(max_fps.and_then(NonZeroU32::new));
+    }
+
+    /// Sets whether the receiver resizes frames that don't match the resolution it negotiated.
+    /// Also picks the filter `sendScaled` uses.
+    #[napi]
+    pub fn set_resize_mode(&mut self, mode: ResizeMode) {
+        self.config = self.config.with_resize_mode(mode.into());
+    }
 
-        sender
-            .try_send_with(self.config, |data| {
-                data[0..frame.len()].copy_from_slice(frame);
+    /// Sets whether the receiver mirrors the image horizontally.
+    #[napi]
+    pub fn set_mirror(&mut self, mode: MirrorMode) {
+        self.config = self.config.with_mirror_mode(mode.into());
+    }
+
+    /// Makes `resize` keep the current size instead of throwing when given invalid dimensions.
+    #[napi]
+    pub fn set_lenient_resize(&mut self, enabled: bool) {
+        self.lenient_resize = enabled;
+    }
+
+    /// Sets how long `send` may wait for the shared memory lock, in milliseconds.
+    ///
+    /// Passing `null` (the default) waits indefinitely.
+    #[napi]
+    pub fn set_lock_timeout(&mut self, timeout_ms: Option<u32>) {
+        self.options.lock_timeout =
+            timeout_ms.map_or(Duration::MAX, |ms| Duration::from_millis(ms.into()));
+        self.apply_options();
+    }
+
+    /// Sets the longest the shared memory lock should be held per frame, in microseconds.
+    /// Frames exceeding it are counted in `stats().overBudget`; `null` disables the check.
+    #[napi]
+    pub fn set_critical_section_budget(&mut self, budget_us: Option<u32>) {
+        self.options.critical_section_budget = budget_us.map(|us| Duration::from_micros(us.into()));
+        self.apply_options();
+    }
+
+    /// Selects the device to send to by its index, where 0 (the default) is the first one.
+    /// `base` replaces the `UnityCapture` prefix of the shared object names, e.g. for a renamed
+    /// fork of the driver. `namespace` selects where those objects are looked up, which
+    /// matters when the driver runs in another session.
+    ///
+    /// Throws, keeping the current device, unless the index is less than `MAX_DEVICES`.
+    /// Selecting another device drops the current connection, so that the next frame connects
+    /// to the new one.
+    #[napi]
+    pub fn set_device(
+        &mut self,
+        index: u32,
+        base: Option<String>,
+        namespace: Option<ObjectNamespace>,
+    ) -> Result<(), napi::Error> {
+        self.options.device = device_names(index, base.as_deref(), namespace)?;
+        self.apply_options();
+        Ok(())
+    }
+
+    /// Sets the named pipe (e.g. `\\.\pipe\UnityCapture`) frames are written to when the shared
+    /// memory can't be opened for good, e.g. because access is denied. A receiver that doesn't
+    /// exist (yet) doesn't trigger the fallback. Takes effect on the next connect; `null`
+    /// disables the fallback.
+    #[napi]
+    pub fn set_pipe_fallback(&mut self, name: Option<String>) {
+        self.options.pipe_fallback = name;
+        self.apply_options();
+    }
+
+    /// Makes `send` wait up to `timeoutMs` for the consumer to ask for a frame before publishing
+    /// it, pacing the frames by the consumer. The frame is published anyway on timeout. `null`
+    /// (the default) publishes right away.
+    ///
+    /// Throws if a timeout is given while an `onFrameRequested` callback is registered.
+    #[napi]
+    pub fn set_want_timeout(&mut self, timeout_ms: Option<u32>) -> Result<(), napi::Error> {
+        if timeout_ms.is_some() {
+            self.ensure_no_frame_watcher("setWantTimeout")?;
+        }
+
+        self.options.want_timeout = timeout_ms.map(|ms| Duration::from_millis(ms.into()));
+        self.apply_options();
+        Ok(())
+    }
+
+    /// Makes `send` accept frames shorter than the configured size, filling the rest of the
+    /// image with zeros (transparent black). Off by default: such frames are rejected with
+    /// `SEND_FRAME_LENGTH`, as are longer ones either way.
+    #[napi]
+    pub fn set_pad_short_frames(&mut self, enabled: bool) {
+        self.options.pad_short_frames = enabled;
+        self.apply_options();
+    }
+
+    /// Makes a failed connect be retried up to `retries` times, sleeping `intervalMs` between
+    /// attempts, before `send` gives up on the frame. The default is 0 (no retries).
+    ///
+    /// `send` blocks while retrying, so keep both small.
+    #[napi]
+    pub fn set_open_retries(&mut self, retries: u32, interval_ms: u32) {
+        self.options.open_retries = retries;
+        self.options.open_retry_interval = Duration::from_millis(interval_ms.into());
+        self.apply_options();
+    }
+
+    /// Sets how the `SENT` event is signaled after each frame. Keep the default (`set`) unless
+    /// the consumer requires otherwise.
+    #[napi]
+    pub fn set_sent_signal(&mut self, signal: SentSignal) {
+        self.options.sent_signal = signal.into();
+        self.apply_options();
+    }
+
+    /// Sets which send failures are silently retried on the next send rather than thrown.
+    #[napi]
+    pub fn set_retry_policy(&mut self, policy: RetryPolicy) {
+        self.retry = policy.classifier();
+    }
+
+    /// Makes `send` skip frames whose pixels are all black (alpha is ignored).
+    ///
+    /// The check runs over every frame; skipped frames are counted in `stats().rejected`.
+    #[napi]
+    pub fn set_reject_black_frames(&mut self, enabled: bool) {
+        self.reject_black_frames = enabled;
+        let validator = self.frame_validator();
+
+        if let Some(sender) = &self.sender {
+            lock(sender).set_frame_validator(validator);
+        }
+    }
+
+    #[napi]
+    pub fn stats(&self) -> Stats {
+        self.sender
+            .as_ref()
+            .map_or_else(SendStats::default, |sender| lock(sender).stats())
+            .into()
+    }
+
+    /// Zeroes the counters returned by `stats`.
+    #[napi]
+    pub fn reset_stats(&mut self) {
+        if let Some(sender) = &self.sender {
+            lock(sender).reset_stats();
+        }
+    }
+
+    /// Zeroes the counters and drops cached state: the next frame rewrites the header,
+    /// `keepAlive` and `applyConfig` fail until a frame is sent, the `setMaxFps` limit starts
+    /// over and `lastWin32Error` is cleared.
+    ///
+    /// Unlike `reset`, the connection to the driver is kept; unlike `resetStats`, which only
+    /// zeroes the counters, the next frame starts from a clean slate.
+    #[napi]
+    pub fn reset_state(&mut self) {
+        if let Some(sender) = &self.sender {
+            lock(sender).reset_state();
+        }
+    }
+
+    /// Drops the connection to the driver, so that the next frame reconnects (e.g. to objects
+    /// the driver recreated). Unlike `stop` followed by `start`, settings and `stats()` are
+    /// kept.
+    #[napi]
+    pub fn reset(&mut self) {
+        if let Some(sender) = &self.sender {
+            lock(sender).reset();
+        }
+    }
+
+    /// Reads the header and frame the driver would see right now, without writing anything.
+    ///
+    /// The shared memory is opened for reading only, so this works even where `send` can't
+    /// connect for lack of write access. Waits at most the lock timeout (see `setLockTimeout`)
+    /// for the lock.
+    #[napi]
+    pub fn snapshot(&self) -> Result<FrameSnapshot, napi::Error> {
+        sender::read_snapshot(&self.options.device, self.options.lock_timeout)
+            .map(FrameSnapshot::from)
+            .map_err(|e| {
+                napi::Error::new(
+                    napi::Status::GenericFailure,
+                    Report::from_error(e).to_string(),
+                )
             })
-            .or_else(|e| {
-                if e.should_retry() {
-                    Ok(())
-                } else {
-                    let message = Report::from_error(e).to_string();
-                    Err(napi::Error::new(napi::Status::GenericFailure, message))
-                }
+    }
+
+    /// Checks the usual reasons for a black camera and reports what it found.
+    ///
+    /// The checks don't connect or send anything. The sender doesn't track the `WANT` event or
+    /// the errors it swallowed while retrying, so those aren't covered.
+    #[napi]
+    pub fn troubleshoot(&self) -> TroubleshootReport {
+        let mut findings = Vec::new();
+
+        let driver_available = match sender::is_driver_available(&self.options.device) {
+            Ok(available) => Some(available),
+            Err(e) => {
+                findings.push(format!(
+                    "checking for the driver failed: {}",
+                    Report::from_error(e)
+                ));
+                None
+            }
+        };
+
+        if driver_available == Some(false) {
+            findings.push("the driver isn't installed, or no application is using it".to_owned());
+        }
+
+        if !self.config.is_valid() {
+            findings.push("the frame size is zero or exceeds the maximum".to_owned());
+        }
+
+        let running = self.is_running();
+        let stats = self.stats();
+
+        if !running {
+            findings.push("the camera isn't running (call `start`)".to_owned());
+        } else if matches!(self.connection_state(), ConnectionState::Disconnected) {
+            findings.push("the camera isn't connected to the driver".to_owned());
+        } else if stats.published == 0 {
+            findings.push("no frame has been published yet".to_owned());
+        }
+
+        // Only senders create `WANT`, so if it exists while this camera holds no handle to it,
+        // another sender has it open (or the driver still has it from a previous one).
+        let holds_want = !matches!(self.connection_state(), ConnectionState::Disconnected)
+            || self.frame_requests.is_some();
+
+        if !holds_want && Event::exists(&self.options.device.want).unwrap_or(false) {
+            findings.push(
+                "the `WANT` event already exists, so another sender may be using the device"
+                    .to_owned(),
+            );
+        }
+
+        if stats.rejected > 0 {
+            findings.push(format!(
+                "{} frames were rejected by the frame validator",
+                stats.rejected
+            ));
+        }
+
+        TroubleshootReport {
+            driver_available,
+            running,
+            connection_state: self.connection_state(),
+            stats,
+            findings,
+        }
+    }
+
+    /// Whether `start` has been called (and `stop` hasn't since).
+    #[napi(getter)]
+    pub fn is_running(&self) -> bool {
+        self.sender.is_some()
+    }
+
+    #[napi(getter)]
+    pub fn width(&self) -> u32 {
+        self.config.width()
+    }
+
+    #[napi(getter)]
+    pub fn height(&self) -> u32 {
+        self.config.height()
+    }
+
+    #[napi(getter)]
+    pub fn connection_state(&self) -> ConnectionState {
+        self.sender
+            .as_ref()
+            .map_or(sender::ConnectionState::Disconnected, |sender| {
+                lock(sender).connection_state()
             })
+            .into()
+    }
+
+    /// The Win32 error code (as returned by `GetLastError`, e.g. 5 for `ERROR_ACCESS_DENIED`)
+    /// behind the most recent failure to connect, send or close the connection, including
+    /// failures retried silently, or `null` if there was none or it didn't come from the OS.
+    #[napi(getter)]
+    pub fn last_win32_error(&self) -> Option<u32> {
+        self.sender
+            .as_ref()
+            .and_then(|sender| lock(sender).last_win32_error())
+    }
+
+    /// Registers a callback invoked (asynchronously) on every connection state transition.
+    /// Passing `null` removes it.
+    #[napi]
+    pub fn on_state_change(&mut self, callback: Option<StateChangeCallback>) {
+        self.on_state_change = callback.map(Arc::new);
+        let listener = self.state_listener();
+
+        if let Some(sender) = &self.sender {
+            lock(sender).set_state_listener(listener);
+        }
+    }
+
+    /// Registers a callback invoked (asynchronously) with a message whenever the camera ignores
+    /// a setting instead of throwing, e.g. an invalid size passed to `resize` while lenient
+    /// resizing is enabled. Passing `null` removes it.
+    #[napi]
+    pub fn on_warning(&mut self, callback: Option<WarningCallback>) {
+        self.on_warning = callback;
+    }
+
+    /// Registers a callback invoked (asynchronously) every time the consumer asks for a new
+    /// frame, so that frames can be sent on demand instead of on a timer. The callback is
+    /// expected to call `send` (or another sending method). Passing `null` removes it.
+    ///
+    /// The callback is bound to the device selected at the time of the call; register it again
+    /// after `setDevice`. If waiting for requests fails, it is called with the error as its
+    /// first argument and not called again.
+    ///
+    /// Each request can only be taken once, and the callback takes them all, so `trySendNow`,
+    /// `sendAndConfirm` and `setWantTimeout`, which wait for requests too, throw while it is
+    /// registered. Likewise, registering it throws while a `WANT` timeout is set.
+    #[napi]
+    pub fn on_frame_requested(
+        &mut self,
+        callback: Option<FrameRequestCallback>,
+    ) -> Result<(), napi::Error> {
+        // Stops the previous watcher, if any, before starting the new one.
+        self.frame_requests = None;
+
+        let Some(callback) = callback else {
+            return Ok(());
+        };
+
+        if self.options.want_timeout.is_some() {
+            return Err(napi::Error::new(
+                napi::Status::GenericFailure,
+                "`onFrameRequested` can't be used while a `WANT` timeout is set (see \
+                 `setWantTimeout`)",
+            ));
+        }
+
+        // Creating the event (rather than opening it) lets the callback be registered before
+        // the sender connects; the sender then opens the same object. If the sender is connected
+        // already, this opens its object instead.
+        let (want_frame, _) = Event::create_new(&self.options.device.want).map_err(|e| {
+            napi::Error::new(
+                napi::Status::GenericFailure,
+                Report::from_error(e).to_string(),
+            )
+        })?;
+
+        self.frame_requests = Some(FrameRequestWatcher::spawn(want_frame, callback));
+        Ok(())
+    }
+
+    #[napi]
+    pub fn start(&mut self) {
+        let sender = self
+            .warm
+            .take()
+            .unwrap_or_else(|| Arc::new(Mutex::new(Sender::new(self.options.clone()))));
+
+        // Settings may have changed while the sender was kept warm.
+        let mut guard = lock(&sender);
+        guard.set_options(self.options.clone());
+        guard.set_frame_validator(self.frame_validator());
+        guard.set_state_listener(self.state_listener());
+        drop(guard);
+
+        self.sender = Some(sender);
+    }
+
+    /// Stops sending. If the camera is kept warm (see `keepWarm`), the connection is kept so
+    /// that the next `start` doesn't have to reconnect.
+    #[napi]
+    pub fn stop(&mut self) {
+        let sender = self.sender.take();
+
+        if self.keep_warm {
+            self.warm = sender.or(self.warm.take());
+        }
+    }
+
+    /// Makes `stop` keep the connection to the driver open, so that `start` can resume right
+    /// away. Disabling it releases a connection kept by a previous `stop`.
+    ///
+    /// A warm camera keeps the driver's mutex, events and the mapped shared memory (about
+    /// 64 MiB of address space) open while stopped.
+    #[napi]
+    pub fn keep_warm(&mut self, enabled: bool) {
+        self.keep_warm = enabled;
+
+        if !enabled {
+            self.warm = None;
+        }
+    }
+
+    /// Stops the camera and releases everything it holds, including a connection kept warm.
+    #[napi]
+    pub fn dispose(&mut self) {
+        self.sender = None;
+        self.warm = None;
+        self.frame_requests = None;
+    }
+
+    fn frame_validator(&self) -> Option<FrameValidator> {
+        self.reject_black_frames
+            .then(|| Box::new(|frame: &[u8]| !convert::is_black(frame)) as FrameValidator)
+    }
+
+    fn state_listener(&self) -> Option<StateListener> {
+        self.on_state_change.clone().map(|callback| {
+            Box::new(move |state: sender::ConnectionState| {
+                callback.call(state.into(), ThreadsafeFunctionCallMode::NonBlocking);
+            }) as StateListener
+        })
+    }
+
+    /// Fails if an `onFrameRequested` callback is registered, since its watcher takes every
+    /// request `method` would wait for.
+    fn ensure_no_frame_watcher(&self, method: &str) -> Result<(), napi::Error> {
+        if self.frame_requests.is_some() {
+            return Err(napi::Error::new(
+                napi::Status::GenericFailure,
+                format!(
+                    "`{method}` can't be used while an `onFrameRequested` callback is registered"
+                ),
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn warn(&self, message: String) {
+        if let Some(callback) = &self.on_warning {
+            callback.call(message, ThreadsafeFunctionCallMode::NonBlocking);
+        }
+    }
+
+    fn apply_options(&mut self) {
+        if let Some(sender) = &self.sender {
+            lock(sender).set_options(self.options.clone());
+        }
+    }
+
+    /// Connects to the driver without sending a frame, returning whether that succeeded (or the
+    /// camera was already connected). Errors the retry policy doesn't retry are thrown, like
+    /// `send` does.
+    #[napi]
+    pub fn probe(&mut self) -> Result<bool, napi::Error> {
+        send_frame(self.sender.as_ref(), self.config, &self.retry, |sender| {
+            sender.try_connect().map(|()| true)
+        })
+    }
+
+    /// Sends `frame`, which must be exactly one frame of the configured size and format (or
+    /// shorter, see `setPadShortFrames`).
+    ///
+    /// Returns the number of image bytes written, which is always the full frame size, or 0 if
+    /// the frame was skipped (rejected, throttled or to be retried).
+    ///
+    /// The message of an error thrown by this or any other sending method starts with a stable
+    /// code followed by a colon, e.g. `SEND_LOCK_TIMEOUT: ...`. Codes of errors raised while
+    /// connecting (retried on the next send by default) start with `INIT_`, the others with
+    /// `SEND_`. The error's `cause` chain holds the underlying errors one by one, down to the
+    /// OS error (with its `HRESULT`), if any.
+    #[napi]
+    pub fn send(&mut self, frame: &[u8]) -> Result<u32, napi::Error> {
+        let config = self.config;
+
+        send_frame(self.sender.as_ref(), config, &self.retry, |sender| {
+            sender.try_send(config, frame).map(|published| {
+                if published {
+                    config.frame_len() as u32
+                } else {
+                    0
+                }
+            })
+        })
+    }
+
+    /// Switches to a `width`×`height` frame size and sends `frame`, which must have exactly that
+    /// size, as its first frame.
+    ///
+    /// The new header and image are written under a single lock, so the consumer never sees
+    /// one without the other. The size is only changed if the frame is valid.
+    #[napi]
+    pub fn reconfigure_and_send(
+        &mut self,
+        width: u32,
+        height: u32,
+        frame: &[u8],
+    ) -> Result<(), napi::Error> {
+        let config = Self::sized_config(self.config, width, height, frame)?;
+        self.config = config;
+
+        send_frame(self.sender.as_ref(), config, &self.retry, |sender| {
+            sender.try_send(config, frame).map(drop)
+        })
+    }
+
+    /// Sends `frame` as a `width`×`height` frame, without changing the configured size (see
+    /// `resize`). `frame` must have exactly that size.
+    #[napi]
+    pub fn send_frame(&mut self, frame: &[u8], width: u32, height: u32) -> Result<(), napi::Error> {
+        let config = Self::sized_config(self.config, width, height, frame)?;
+
+        send_frame(self.sender.as_ref(), config, &self.retry, |sender| {
+            sender.try_send(config, frame).map(drop)
+        })
+    }
+
+    /// Sends `frame` with some of the configuration overridden for this frame only. `frame`
+    /// must match the resulting size and format.
+    #[napi]
+    pub fn send_with_options(
+        &mut self,
+        frame: &[u8],
+        options: SendOptions,
+    ) -> Result<(), napi::Error> {
+        let config = with_overrides(self.config, options.format, options.mirror, options.resize);
+        let width = options.width.unwrap_or(config.width());
+        let height = options.height.unwrap_or(config.height());
+        let config = Self::sized_config(config, width, height, frame)?;
+
+        send_frame(self.sender.as_ref(), config, &self.retry, |sender| {
+            sender.try_send(config, frame).map(drop)
+        })
+    }
+
+    /// Changes several settings at once, keeping the sender. Missing fields keep their
+    /// current value. Nothing is changed if a setting is invalid, e.g. `base` or `namespace`
+    /// is given without `deviceIndex`.
+    ///
+    /// Selecting another device drops the connection (see `setDevice`); otherwise the
+    /// connection is kept.
+    #[napi]
+    pub fn reconfigure(&mut self, options: ReconfigureOptions) -> Result<(), napi::Error> {
+        if options.device_index.is_none() && (options.base.is_some() || options.namespace.is_some())
+        {
+            return Err(napi::Error::new(
+                napi::Status::InvalidArg,
+                "`base` and `namespace` require `deviceIndex`",
+            ));
+        }
+
+        let device = options
+            .device_index
+            .map(|index| device_names(index, options.base.as_deref(), options.namespace))
+            .transpose()?;
+
+        let config = with_overrides(self.config, options.format, options.mirror, options.resize);
+        let width = options.width.unwrap_or(config.width());
+        let height = options.height.unwrap_or(config.height());
+
+        self.config = Self::resized_config(config, width, height)?;
+
+        if let Some(device) = device {
+            self.options.device = device;
+            self.apply_options();
+        }
+
+        Ok(())
+    }
+
+    /// Returns `config` with a `width`×`height` frame size, checking that `frame` matches it.
+    fn sized_config(
+        config: FrameConfig,
+        width: u32,
+        height: u32,
+        frame: &[u8],
+    ) -> Result<FrameConfig, napi::Error> {
+        let config = Self::resized_config(config, width, height)?;
+
+        if frame.len() != config.frame_len() {
+            return Err(napi::Error::new(
+                napi::Status::InvalidArg,
+                format!(
+                    "the frame is {} bytes long, expected {}",
+                    frame.len(),
+                    config.frame_len()
+                ),
+            ));
+        }
+
+        Ok(config)
+    }
+
+    /// Returns `config` with a `width`×`height` frame size, or an error if it isn't valid.
+    fn resized_config(
+        config: FrameConfig,
+        width: u32,
+        height: u32,
+    ) -> Result<FrameConfig, napi::Error> {
+        config.resized(width, height).ok_or_else(|| {
+            napi::Error::new(
+                napi::Status::InvalidArg,
+                format!(
+                    "the frame size ({width}x{height}) must be non-zero and at most \
+                     {MAX_WIDTH}x{MAX_HEIGHT}"
+                ),
+            )
+        })
+    }
+
+    /// Sends a frame of R10G10B10A2 pixels, down-converting it to 8 bits per channel during
+    /// the copy, since the driver has no 10-bit format. The frame is sent as `uint8`
+    /// regardless of the configured format.
+    ///
+    /// Each pixel is a little-endian 32-bit integer holding red in bits 0-9, green in bits
+    /// 10-19, blue in bits 20-29 and alpha in bits 30-31. The frame must have exactly
+    /// width×height pixels.
+    #[napi]
+    pub fn send_rgb10a2(&mut self, frame: &[u8]) -> Result<(), napi::Error> {
+        let config = self.config.with_format(sender::PixelFormat::Uint8);
+        let frame_len = config.frame_len();
+
+        if frame.len() != frame_len {
+            return Err(napi::Error::new(
+                napi::Status::InvalidArg,
+                format!(
+                    "the frame is {} bytes long, expected {frame_len}",
+                    frame.len()
+                ),
+            ));
+        }
+
+        send_frame(self.sender.as_ref(), config, &self.retry, |sender| {
+            sender
+                .try_send_with(config, |data| convert::rgb10a2_to_rgba(frame, data))
+                .map(drop)
+        })
+    }
+
+    /// Sends a `width`×`height` frame of RGB pixels, three bytes each, expanding it to opaque
+    /// RGBA during the copy, without changing the configured size. Rows start `stride` bytes
+    /// apart (`width * 3`, i.e. tightly packed, by default), so padded rows such as FFmpeg's
+    /// can be sent as is. The frame is sent as `uint8` regardless of the configured format.
+    ///
+    /// The receiver always gets tightly packed RGBA rows, so no padding is written.
+    #[napi]
+    pub fn send_rgb24(
+        &mut self,
+        frame: &[u8],
+        width: u32,
+        height: u32,
+        stride: Option<u32>,
+    ) -> Result<(), napi::Error> {
+        let config = Self::resized_config(
+            self.config.with_format(sender::PixelFormat::Uint8),
+            width,
+            height,
+        )?;
+        let (width, rows) = (width as usize, height as usize);
+        let stride = stride.map_or(width * 3, |stride| stride as usize);
+
+        check_strided_frame(config, frame, stride, width * 3)?;
+
+        send_frame(self.sender.as_ref(), config, &self.retry, |sender| {
+            sender
+                .try_send_with(config, |data| {
+                    convert::rgb_rows_to_rgba(frame, stride, width, rows, data)
+                })
+                .map(drop)
+        })
+    }
+
+    /// Sends `frame`, inferring its format from its length: width×height bytes are grayscale,
+    /// three times that RGB and four times RGBA. Grayscale and RGB frames are expanded to
+    /// opaque RGBA during the copy. The frame is sent as `uint8` regardless of the configured
+    /// format.
+    ///
+    /// Meant for prototyping; prefer `send`, which doesn't guess, in production.
+    #[napi]
+    pub fn send_auto(&mut self, frame: &[u8]) -> Result<(), napi::Error> {
+        let config = self.config.with_format(sender::PixelFormat::Uint8);
+        let pixels = config.frame_len() / sender::BYTES_PER_PIXEL;
+
+        let channels = match frame.len() / pixels.max(1) {
+            channels @ (1 | 3 | 4) if frame.len() == pixels * channels => channels,
+            _ => {
+                return Err(napi::Error::new(
+                    napi::Status::InvalidArg,
+                    format!(
+                        "the frame is {} bytes long, expected {pixels} (grayscale), {} (RGB) \
+                         or {} (RGBA)",
+                        frame.len(),
+                        pixels * 3,
+                        pixels * 4
+                    ),
+                ));
+            }
+        };
+
+        let is_rgba = channels == sender::BYTES_PER_PIXEL;
+
+        send_frame(self.sender.as_ref(), config, &self.retry, |sender| {
+            if is_rgba {
+                return sender.try_send(config, frame).map(drop);
+            }
+
+            sender
+                .try_send_with(config, |data| {
+                    convert::expand_to_rgba(frame, channels, data)
+                })
+                .map(drop)
+        })
+    }
+
+    /// Sends a frame of BGR (`bytesPerPixel` = 3) or BGRA (`bytesPerPixel` = 4) pixels,
+    /// converting it to RGBA during the copy. The frame is sent as `uint8` regardless of the
+    /// configured format.
+    #[napi]
+    pub fn send_bgr(&mut self, frame: &[u8], bytes_per_pixel: u32) -> Result<(), napi::Error> {
+        let invalid_arg = |message: String| napi::Error::new(napi::Status::InvalidArg, message);
+
+        if !matches!(bytes_per_pixel, 3 | 4) {
+            return Err(invalid_arg(format!(
+                "`bytesPerPixel` must be 3 or 4, got {bytes_per_pixel}"
+            )));
+        }
+
+        let config = self.config.with_format(sender::PixelFormat::Uint8);
+        let pixel_size = bytes_per_pixel as usize;
+        let expected = config.frame_len() / sender::BYTES_PER_PIXEL * pixel_size;
+
+        if frame.len() != expected {
+            return Err(invalid_arg(format!(
+                "the frame is {} bytes long, expected {expected}",
+                frame.len()
+            )));
+        }
+
+        send_frame(self.sender.as_ref(), config, &self.retry, |sender| {
+            sender
+                .try_send_with(config, |data| convert::bgr_to_rgba(frame, pixel_size, data))
+                .map(drop)
+        })
+    }
+
+    /// Sends a frame of linear half-float RGBA pixels, one value per channel, as is. The frame is
+    /// sent as `fp16Linear` regardless of the configured format.
+    #[napi]
+    pub fn send_fp16(&mut self, frame: Uint16Array) -> Result<(), napi::Error> {
+        let config = self.config.with_format(sender::PixelFormat::Fp16Linear);
+        let expected = config.frame_len() / size_of::<u16>();
+
+        if frame.len() != expected {
+            return Err(napi::Error::new(
+                napi::Status::InvalidArg,
+                format!("the frame has {} values, expected {expected}", frame.len()),
+            ));
+        }
+
+        send_frame(self.sender.as_ref(), config, &self.retry, |sender| {
+            sender
+                .try_send_with(config, |data| convert::copy_u16(&frame, data))
+                .map(drop)
+        })
+    }
+
+    /// Sends a `srcWidth`×`srcHeight` RGBA frame scaled to the configured size on the CPU,
+    /// with bilinear interpolation, or nearest-neighbor if the resize mode is `disabled`. The
+    /// frame is sent as `uint8` regardless of the configured format.
+    #[napi]
+    pub fn send_scaled(
+        &mut self,
+        frame: &[u8],
+        src_width: u32,
+        src_height: u32,
+    ) -> Result<(), napi::Error> {
+        let invalid_arg = |message: String| napi::Error::new(napi::Status::InvalidArg, message);
+
+        let config = self.config.with_format(sender::PixelFormat::Uint8);
+        let (src_width, src_height) = (src_width as usize, src_height as usize);
+        let expected = src_width.saturating_mul(src_height).saturating_mul(4);
+
+        if src_width == 0 || src_height == 0 {
+            return Err(invalid_arg(format!(
+                "the source size ({src_width}x{src_height}) must be non-zero"
+            )));
+        }
+
+        if frame.len() != expected {
+            return Err(invalid_arg(format!(
+                "the frame is {} bytes long, expected {expected}",
+                frame.len()
+            )));
+        }
+
+        let (width, height) = (config.width() as usize, config.height() as usize);
+
+        send_frame(self.sender.as_ref(), config, &self.retry, |sender| {
+            sender
+                .try_send_with(config, |data| {
+                    let scale = match config.resize_mode() {
+                        sender::ResizeMode::Disabled => convert::scale_nearest,
+                        sender::ResizeMode::Linear => convert::scale_bilinear,
+                    };
+
+                    scale(frame, src_width, src_height, data, width, height)
+                })
+                .map(drop)
+        })
+    }
+
+    /// Like `send`, but publishes from the libuv thread pool, so that waiting for the lock (or
+    /// for the consumer, see `setWantTimeout`) doesn't block the event loop. The returned
+    /// promise rejects with the errors `send` would throw.
+    ///
+    /// `frame` must not be modified until the promise settles. Frames sent without awaiting
+    /// the previous promise aren't guaranteed to be published in order.
+    #[napi]
+    pub fn send_async(&self, frame: Buffer) -> AsyncTask<SendTask> {
+        AsyncTask::new(self.send_task(frame))
+    }
+
+    fn send_task(&self, frame: Buffer) -> SendTask {
+        SendTask {
+            sender: self.sender.clone(),
+            config: self.config,
+            frame,
+            retry: self.retry.clone(),
+        }
+    }
+
+    /// Sends `frames` back to back, stopping at the first error. Each frame is written under
+    /// its own lock, since the consumer needs the lock to read it. To present every frame in
+    /// order, make each one wait for the consumer to ask for it with `setWantTimeout`.
+    ///
+    /// Returns the number of frames published. If a frame fails with a non-retryable error,
+    /// the thrown error reports how many frames were sent before it.
+    #[napi]
+    pub fn send_batch(&mut self, frames: Vec<Buffer>) -> Result<u32, napi::Error> {
+        let frames: Vec<&[u8]> = frames.iter().map(|frame| &frame[..]).collect();
+
+        let mut sender = running_sender(self.sender.as_ref(), self.config)?;
+        let (sent, result) = sender.try_send_batch(self.config, &frames);
+
+        match result {
+            Err(e) if !(self.retry)(&e) => Err(send_error(
+                e,
+                Some(format!("sent {sent} of {} frames", frames.len())),
+            )),
+            _ => Ok(sent as u32),
+        }
+    }
+
+    /// Sends a `width`×`height` frame whose rows start `stride` bytes apart in `buffer`,
+    /// repacking the rows tightly. The frame doesn't have to match the configured size, which
+    /// is left unchanged; everything else (format, mirroring, ...) applies as configured.
+    #[napi]
+    pub fn send_strided(
+        &mut self,
+        buffer: &[u8],
+        stride: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), napi::Error> {
+        let config = Self::resized_config(self.config, width, height)?;
+        let (row_len, rows, stride) = (config.row_len(), height as usize, stride as usize);
+
+        check_strided_frame(config, buffer, stride, row_len)?;
+
+        send_frame(self.sender.as_ref(), config, &self.retry, |sender| {
+            sender
+                .try_send_with(config, |data| {
+                    convert::copy_rows(buffer, stride, data, row_len, row_len, rows);
+                })
+                .map(drop)
+        })
+    }
+
+    /// Like `send`, but reads the frame from `length` bytes of native memory at `address`, e.g.
+    /// a buffer owned by another addon, avoiding a round trip through a `Buffer`.
+    ///
+    /// This is inherently unsafe: `address` must point to at least `length` readable bytes
+    /// that stay valid, and aren't written to, until the call returns. Anything else may crash
+    /// the process or send garbage. The length is checked against the configured size before
+    /// the memory is read.
+    #[napi]
+    pub fn send_pointer(&mut self, address: BigInt, length: u32) -> Result<u32, napi::Error> {
+        let invalid_arg = |message: String| napi::Error::new(napi::Status::InvalidArg, message);
+
+        let (negative, address, lossless) = address.get_u64();
+
+        let address = usize::try_from(address)
+            .ok()
+            .filter(|&address| !negative && lossless && address != 0)
+            .ok_or_else(|| invalid_arg("`address` isn't a valid pointer".to_owned()))?;
+
+        let (length, expected) = (length as usize, self.config.frame_len());
+
+        if length != expected {
+            return Err(invalid_arg(format!(
+                "the frame is {length} bytes long, expected {expected}"
+            )));
+        }
+
+        sender::check_frame_size(&self.config)
+            .map_err(|source| send_error(sender::Error::Send { source }, None))?;
+
+        // SAFETY:
+        // - The caller promises that `address` points to `length` readable bytes that don't
+        //   change during the call.
+        // - `address` isn't null, and `length` doesn't exceed `MAX_IMAGE_SIZE` (checked
+        //   above), let alone `isize::MAX`.
+        let frame = unsafe { std::slice::from_raw_parts(address as *const u8, length) };
+
+        self.send(frame)
+    }
+
+    /// Sends a frame that `callback` writes in place, avoiding the copy from a JS buffer.
+    ///
+    /// `callback` is given an `ArrayBuffer` over the frame's bytes in the shared memory, exactly
+    /// one frame of the configured size and format long, to be wrapped in a typed array. The
+    /// buffer is detached once `callback` returns, so it can't be written to after the frame is
+    /// published. Where the runtime doesn't allow external buffers (e.g. Electron), `callback`
+    /// writes to a copy instead, which is copied into the shared memory afterwards.
+    ///
+    /// `callback` runs while the shared memory is locked, and must not use the camera. If it
+    /// throws, whatever it wrote is published anyway and the error is rethrown.
+    ///
+    /// Returns whether the frame was published, i.e. `false` if it was skipped (throttled or to
+    /// be retried), without calling `callback`.
+    #[napi]
+    pub fn send_with(
+        &mut self,
+        env: Env,
+        callback: Function<ArrayBuffer, Unknown>,
+    ) -> Result<bool, napi::Error> {
+        self.send_in_place(|data| {
+            let ptr = data.as_mut_ptr();
+
+            // SAFETY: the mapping outlives the buffer, which is detached (or was a copy) before
+            // this returns, and nothing else accesses `data` until then.
+            let buffer =
+                unsafe { ArrayBuffer::from_external(&env, ptr, data.len(), (), |_, ()| {}) }?;
+            let called = callback.call(buffer).map(drop);
+
+            if buffer.as_ptr() == ptr.cast_const() {
+                buffer.detach()?;
+            } else {
+                data.copy_from_slice(&buffer);
+            }
+
+            called
+        })
+    }
+
+    /// Publishes a frame of the configured size and format written into the image region by
+    /// `f`, returning `false` if it was skipped without calling `f`. An error from `f` is
+    /// returned once the frame has been published.
+    fn send_in_place(
+        &mut self,
+        f: impl FnOnce(&mut [u8]) -> Result<(), napi::Error>,
+    ) -> Result<bool, napi::Error> {
+        let config = self.config;
+        let mut written = Ok(());
+
+        let published = send_frame(self.sender.as_ref(), config, &self.retry, |sender| {
+            sender.try_send_with(config, |data| written = f(data))
+        })?;
+
+        written.map(|()| published)
+    }
+
+    /// Publishes `blob`, a complete `header + image` as laid out in the shared memory (e.g.
+    /// produced by another camera), as is. The camera's own configuration isn't used for the
+    /// frame.
+    ///
+    /// Throws if the embedded header doesn't describe a valid frame or `blob` isn't exactly as
+    /// long as the header and that frame.
+    #[napi]
+    pub fn send_raw(&mut self, blob: &[u8]) -> Result<(), napi::Error> {
+        send_frame(self.sender.as_ref(), self.config, &self.retry, |sender| {
+            sender.try_send_raw(blob)
+        })
+    }
+
+    /// Rewrites the frame header from the current configuration and republishes the last
+    /// sent image, without copying a new one. Fails if no frame has been sent yet.
+    #[napi]
+    pub fn apply_config(&mut self) -> Result<(), napi::Error> {
+        send_frame(self.sender.as_ref(), self.config, &self.retry, |sender| {
+            sender.try_apply_config(self.config)
+        })
+    }
+
+    /// Like `send`, but only sends `frame` if the consumer is asking for a frame right now.
+    /// Returns what happened to the frame (see `SendOutcome`); never waits for the consumer.
+    /// Throws while an `onFrameRequested` callback is registered.
+    #[napi]
+    pub fn try_send_now(&mut self, frame: &[u8]) -> Result<SendOutcome, napi::Error> {
+        self.ensure_no_frame_watcher("trySendNow")?;
+
+        send_frame(self.sender.as_ref(), self.config, &self.retry, |sender| {
+            sender
+                .try_send_if_wanted(self.config, frame)
+                .map(SendOutcome::from)
+        })
+    }
+
+    /// Signals the last sent frame again without copying anything, so that the receiver's frame
+    /// timeout (see `setFrameTimeout`) doesn't expire while the source is paused. Fails if no
+    /// frame has been sent since connecting.
+    #[napi]
+    pub fn keep_alive(&mut self) -> Result<(), napi::Error> {
+        send_frame(self.sender.as_ref(), self.config, &self.retry, |sender| {
+            sender.try_keep_alive()
+        })
+    }
+
+    /// Sends `frame`, then waits up to `timeoutMs` for the consumer to signal the existing
+    /// event named `eventName`.
+    ///
+    /// Returns `true` if the event was signaled in time.
+    #[napi]
+    pub fn send_and_wait(
+        &mut self,
+        frame: &[u8],
+        event_name: String,
+        timeout_ms: u32,
+    ) -> Result<bool, napi::Error> {
+        let event = match &mut self.completion_event {
+            Some((name, event)) if *name == event_name => event,
+            slot => {
+                let event = Event::open_existing_waitable(&event_name).map_err(|e| {
+                    napi::Error::new(
+                        napi::Status::GenericFailure,
+                        Report::from_error(e).to_string(),
+                    )
+                })?;
+
+                &slot.insert((event_name, event)).1
+            }
+        };
+
+        let timeout = Duration::from_millis(timeout_ms.into());
+
+        send_frame(self.sender.as_ref(), self.config, &self.retry, |sender| {
+            sender.try_send_and_wait(self.config, frame, event, timeout)
+        })
+    }
+
+    /// Sends `frame`, then waits up to `timeoutMs` for the consumer to ask for the next frame,
+    /// as a sign that this one was delivered.
+    ///
+    /// Frames sent over the pipe fallback are never confirmed. Throws while an
+    /// `onFrameRequested` callback is registered.
+    #[napi]
+    pub fn send_and_confirm(
+        &mut self,
+        frame: &[u8],
+        timeout_ms: u32,
+    ) -> Result<SendConfirmation, napi::Error> {
+        self.ensure_no_frame_watcher("sendAndConfirm")?;
+
+        let timeout = Duration::from_millis(timeout_ms.into());
+
+        send_frame(self.sender.as_ref(), self.config, &self.retry, |sender| {
+            sender
+                .try_send_and_confirm(self.config, frame, timeout)
+                .map(SendConfirmation::from)
+        })
+    }
+
+    /// Registers a ring of equally sized frame slots that `sendSlot` publishes from.
+    ///
+    /// The buffer is shared with JS, not copied, so slots can be refilled in place between
+    /// sends. Its length must be a multiple of `slots`.
+    #[napi]
+    pub fn register_ring(&mut self, ring: Uint8Array, slots: u32) -> Result<(), napi::Error> {
+        if slots == 0 {
+            return Err(napi::Error::new(
+                napi::Status::InvalidArg,
+                "`slots` must not be zero",
+            ));
+        }
+
+        let slots = slots as usize;
+
+        if !ring.len().is_multiple_of(slots) {
+            return Err(napi::Error::new(
+                napi::Status::InvalidArg,
+                format!(
+                    "the ring length ({}) isn't a multiple of the slot count ({slots})",
+                    ring.len()
+                ),
+            ));
+        }
+
+        let slot_len = ring.len() / slots;
+
+        if slot_len > sender::MAX_IMAGE_SIZE {
+            return Err(napi::Error::new(
+                napi::Status::InvalidArg,
+                format!(
+                    "the slot size ({slot_len}) exceeds the maximum image size ({})",
+                    sender::MAX_IMAGE_SIZE
+                ),
+            ));
+        }
+
+        self.ring = Some(Ring {
+            buffer: ring,
+            slot_len,
+        });
+
+        Ok(())
+    }
+
+    /// Releases the ring registered with `registerRing`.
+    #[napi]
+    pub fn clear_ring(&mut self) {
+        self.ring = None;
+    }
+
+    /// Publishes slot `index` of the registered ring.
+    #[napi]
+    pub fn send_slot(&mut self, index: u32) -> Result<(), napi::Error> {
+        let ring = self.ring.as_ref().ok_or_else(|| {
+            napi::Error::new(napi::Status::GenericFailure, "no ring is registered")
+        })?;
+
+        let slot = ring.slot(index as usize).ok_or_else(|| {
+            napi::Error::new(
+                napi::Status::InvalidArg,
+                format!("slot index {index} is out of range"),
+            )
+        })?;
+
+        send_frame(self.sender.as_ref(), self.config, &self.retry, |sender| {
+            sender.try_send(self.config, slot).map(drop)
+        })
+    }
+}
+
+/// Collects a camera's settings up front, as an alternative to a series of setter calls on
+/// the camera. Setters return the builder, so they can be chained:
+/// `new CameraBuilder(1280, 720).format('fp16Linear').mirror('horizontal').build()`.
+#[napi]
+pub struct CameraBuilder {
+    width: u32,
+    height: u32,
+    device_index: u32,
+    format: sender::PixelFormat,
+    mirror_mode: sender::MirrorMode,
+    resize_mode: sender::ResizeMode,
+    frame_timeout_ms: Option<u32>,
+}
+
+#[napi]
+impl CameraBuilder {
+    #[napi(constructor)]
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            device_index: 0,
+            format: sender::PixelFormat::Uint8,
+            mirror_mode: sender::MirrorMode::Disabled,
+            resize_mode: sender::ResizeMode::Linear,
+            frame_timeout_ms: None,
+        }
+    }
+
+    /// See the `deviceIndex` parameter of the `Camera` constructor.
+    #[napi]
+    pub fn device<'env>(&mut self, this: This<'env>, index: u32) -> This<'env> {
+        self.device_index = index;
+        this
+    }
+
+    /// See `Camera.setFormat`.
+    #[napi]
+    pub fn format<'env>(&mut self, this: This<'env>, format: PixelFormat) -> This<'env> {
+        self.format = format.into();
+        this
+    }
+
+    /// See `Camera.setMirror`.
+    #[napi]
+    pub fn mirror<'env>(&mut self, this: This<'env>, mode: MirrorMode) -> This<'env> {
+        self.mirror_mode = mode.into();
+        this
+    }
+
+    /// See `Camera.setResizeMode`.
+    #[napi]
+    pub fn resize_mode<'env>(&mut self, this: This<'env>, mode: ResizeMode) -> This<'env> {
+        self.resize_mode = mode.into();
+        this
+    }
+
+    /// See `Camera.setFrameTimeout`.
+    #[napi]
+    pub fn frame_timeout<'env>(&mut self, this: This<'env>, timeout_ms: Option<u32>) -> This<'env> {
+        self.frame_timeout_ms = timeout_ms;
+        this
+    }
+
+    /// Creates the camera, throwing if a setting is invalid.
+    #[napi]
+    pub fn build(&self) -> Result<Camera, napi::Error> {
+        let mut camera = Camera::new(self.width, self.height, Some(self.device_index))?;
+
+        camera.config = camera
+            .config
+            .with_format(self.format)
+            .with_mirror_mode(self.mirror_mode)
+            .with_resize_mode(self.resize_mode);
+
+        camera.set_frame_timeout(self.frame_timeout_ms)?;
+
+        Ok(camera)
+    }
+}
+
+/// The receiving end of a device, standing in for the driver: it creates the device's shared
+/// objects and reads the frames cameras send to it, e.g. to test an application without the
+/// driver installed.
+///
+/// Creating one fails while the driver (or another receiver) is using the device.
+#[napi]
+pub struct Receiver {
+    receiver: receiver::Receiver,
+}
+
+#[napi]
+impl Receiver {
+    /// Creates the shared objects of the device with index `deviceIndex` (0 by default; see
+    /// `Camera.setDevice`).
+    #[napi(constructor)]
+    pub fn new(device_index: Option<u32>) -> Result<Self, napi::Error> {
+        let names = device_names(device_index.unwrap_or(0), None, None)?;
+
+        receiver::Receiver::create(names)
+            .map(|receiver| Receiver { receiver })
+            .map_err(receive_error)
+    }
+
+    /// Checks whether a camera has connected, i.e. whether frames can be requested.
+    #[napi]
+    pub fn has_sender(&self) -> Result<bool, napi::Error> {
+        // Cameras create `WANT` when they connect (see `requestFrame`).
+        Event::exists(&self.receiver.names().want).map_err(receive_error)
+    }
+
+    /// Asks the camera for a frame (see `Camera.onFrameRequested`). Throws if no camera has
+    /// connected yet.
+    #[napi]
+    pub fn request_frame(&self) -> Result<(), napi::Error> {
+        self.receiver.request_frame().map_err(receive_error)
+    }
+
+    /// Waits up to `timeoutMs` milliseconds for a camera to send a frame, blocking the event
+    /// loop, and returns it, or `null` if none arrived in time.
+    #[napi]
+    pub fn wait_frame(&mut self, timeout_ms: u32) -> Result<Option<FrameSnapshot>, napi::Error> {
+        self.receiver
+            .wait_frame(Duration::from_millis(timeout_ms.into()))
+            .map(|snapshot| snapshot.map(FrameSnapshot::from))
+            .map_err(receive_error)
+    }
+
+    /// Returns the current header and frame, whether or not a new one has been sent.
+    #[napi]
+    pub fn snapshot(&mut self) -> Result<FrameSnapshot, napi::Error> {
+        self.receiver
+            .snapshot()
+            .map(FrameSnapshot::from)
+            .map_err(receive_error)
+    }
+}
+
+fn receive_error(e: impl std::error::Error) -> napi::Error {
+    napi::Error::new(
+        napi::Status::GenericFailure,
+        Report::from_error(e).to_string(),
+    )
+}
+
+/// Returns the names of the device with index `index`, checking it against `MAX_DEVICES`.
+/// `base` and `namespace` are as in `Camera.setDevice`.
+fn device_names(
+    index: u32,
+    base: Option<&str>,
+    namespace: Option<ObjectNamespace>,
+) -> Result<DeviceNames, napi::Error> {
+    let base = base.unwrap_or(sender::OBJECT_NAME_BASE);
+    let prefix = namespace.map_or("", |namespace| namespace.prefix());
+
+    DeviceNames::try_for_device(base, index)
+        .map(|names| names.with_prefix(prefix))
+        .map_err(|e| napi::Error::new(napi::Status::InvalidArg, Report::from_error(e).to_string()))
+}
+
+/// Returns `config` with the given settings, where present, instead.
+fn with_overrides(
+    mut config: FrameConfig,
+    format: Option<PixelFormat>,
+    mirror: Option<MirrorMode>,
+    resize: Option<ResizeMode>,
+) -> FrameConfig {
+    if let Some(format) = format {
+        config = config.with_format(format.into());
+    }
+
+    if let Some(mirror) = mirror {
+        config = config.with_mirror_mode(mirror.into());
+    }
+
+    if let Some(resize) = resize {
+        config = config.with_resize_mode(resize.into());
+    }
+
+    config
+}
+
+/// Checks that `buffer` holds the rows of a frame of `config`'s height, `row_len` bytes each
+/// and starting `stride` bytes apart.
+fn check_strided_frame(
+    config: FrameConfig,
+    buffer: &[u8],
+    stride: usize,
+    row_len: usize,
+) -> Result<(), napi::Error> {
+    convert::check_strided(buffer.len(), stride, row_len, config.height() as usize)
+        .map_err(|e| napi::Error::new(napi::Status::InvalidArg, Report::from_error(e).to_string()))
+}
+
+/// Runs `f` against a running, configured sender, treating errors `should_retry` accepts as
+/// "nothing happened" (`B::default()`).
+fn send_frame<B, F>(
+    sender: Option<&SharedSender>,
+    config: FrameConfig,
+    should_retry: &RetryClassifier,
+    f: F,
+) -> Result<B, napi::Error>
+where
+    B: Default,
+    F: FnOnce(&mut Sender) -> Result<B, sender::Error>,
+{
+    f(&mut *running_sender(sender, config)?).or_else(|e| {
+        if should_retry(&e) {
+            Ok(B::default())
+        } else {
+            Err(send_error(e, None))
+        }
+    })
+}
+
+/// Converts a send error to a JS error whose message starts with the error's code (see
+/// `Camera.send`), optionally followed by `context`.
+///
+/// The error's `cause` chain mirrors its sources, one message per level, so that they can be
+/// inspected without parsing the message.
+fn send_error(e: sender::Error, context: Option<String>) -> napi::Error {
+    let code = e.code();
+    let cause = error_cause(&e);
+    let report = Report::from_error(e);
+
+    let message = match context {
+        Some(context) => format!("{code}: {context}: {report}"),
+        None => format!("{code}: {report}"),
+    };
+
+    let mut error = napi::Error::new(napi::Status::GenericFailure, message);
+
+    if let Some(cause) = cause {
+        error.set_cause(cause);
+    }
+
+    error
+}
+
+/// Converts the sources of `e` to a chain of JS errors. OS errors carry their `HRESULT`.
+fn error_cause(e: &dyn std::error::Error) -> Option<napi::Error> {
+    let source = e.source()?;
+
+    let message = match source.downcast_ref::<windows::core::Error>() {
+        Some(os) => format!("{} (HRESULT {:#010x})", os.message(), os.code().0),
+        None => source.to_string(),
+    };
+
+    let mut error = napi::Error::new(napi::Status::GenericFailure, message);
+
+    if let Some(cause) = error_cause(source) {
+        error.set_cause(cause);
+    }
+
+    Some(error)
+}
+
+/// Returns the locked sender if the camera is running and configured.
+fn running_sender(
+    sender: Option<&SharedSender>,
+    config: FrameConfig,
+) -> Result<MutexGuard<'_, Sender>, napi::Error> {
+    if !config.is_valid() {
+        return Err(napi::Error::new(
+            napi::Status::GenericFailure,
+            "the camera isn't configured",
+        ));
+    }
+
+    sender
+        .map(lock)
+        .ok_or_else(|| napi::Error::new(napi::Status::GenericFailure, "the camera isn't running"))
+}
+
+/// A sender shared with the tasks of `sendAsync`, which use it from the thread pool.
+type SharedSender = Arc<Mutex<Sender>>;
+
+fn lock(sender: &SharedSender) -> MutexGuard<'_, Sender> {
+    // A panic while sending doesn't leave the sender in a state worth refusing to use.
+    sender.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Publishes a frame from the thread pool for `Camera.sendAsync`.
+pub struct SendTask {
+    sender: Option<SharedSender>,
+    config: FrameConfig,
+    frame: Buffer,
+    retry: RetryClassifier,
+}
+
+impl Task for SendTask {
+    type Output = ();
+    type JsValue = ();
+
+    fn compute(&mut self) -> Result<(), napi::Error> {
+        send_frame(self.sender.as_ref(), self.config, &self.retry, |sender| {
+            sender.try_send(self.config, &self.frame).map(drop)
+        })
+    }
+
+    fn resolve(&mut self, _env: Env, output: ()) -> Result<(), napi::Error> {
+        Ok(output)
+    }
+}
+
+/// A background thread calling a JS callback every time `WANT` is signaled. Stopped on drop.
+struct FrameRequestWatcher {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl FrameRequestWatcher {
+    /// How often the thread checks whether it should stop.
+    const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+    fn spawn(want_frame: Event, callback: FrameRequestCallback) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let thread = thread::spawn({
+            let stop = stop.clone();
+
+            move || {
+                while !stop.load(Ordering::Relaxed) {
+                    match want_frame.wait_timeout(Self::POLL_INTERVAL) {
+                        Ok(()) => {
+                            callback.call(Ok(()), ThreadsafeFunctionCallMode::NonBlocking);
+                        }
+                        Err(WaitEventError::Timeout) => {}
+                        Err(e) => {
+                            let error = napi::Error::new(
+                                napi::Status::GenericFailure,
+                                format!(
+                                    "stopped watching for frame requests: {}",
+                                    Report::from_error(e)
+                                ),
+                            );
+
+                            callback.call(Err(error), ThreadsafeFunctionCallMode::NonBlocking);
+                            break;
+                        }
+                    }
+                }
+            }
+        });
+
+        Self {
+            stop,
+            thread: Some(thread),
+        }
+    }
+}
+
+impl Drop for FrameRequestWatcher {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+
+        if let Some(thread) = self.thread.take() {
+            // The thread never panics, and a failure in it has already been passed to the
+            // callback.
+            let _ = thread.join();
+        }
+    }
+}
+
+/// A JS-owned buffer split into equally sized frame slots.
+struct Ring {
+    buffer: Uint8Array,
+    slot_len: usize,
+}
+
+impl Ring {
+    fn slot(&self, index: usize) -> Option<&[u8]> {
+        self.buffer.chunks_exact(self.slot_len).nth(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a running `width`×`height` camera and the receiver it sends to, on a device no
+    /// other test (or process) uses.
+    fn camera_with_receiver(name: &str, width: u32, height: u32) -> (Camera, receiver::Receiver) {
+        let base = format!("node-vcam-test-{}-{name}", std::process::id());
+        let receiver = receiver::Receiver::create(DeviceNames::for_device(&base, 0)).unwrap();
+
+        let mut camera = Camera::new(width, height, None).unwrap();
+        camera.set_device(0, Some(base), None).unwrap();
+        camera.start();
+
+        (camera, receiver)
+    }
+
+    /// Returns a running `width`×`height` camera for a device no receiver has created.
+    fn camera_without_receiver(name: &str, width: u32, height: u32) -> Camera {
+        let base = format!("node-vcam-test-{}-{name}", std::process::id());
+
+        let mut camera = Camera::new(width, height, None).unwrap();
+        camera.set_device(0, Some(base), None).unwrap();
+        camera.start();
+
+        camera
+    }
+
+    /// Returns the frame `receiver` was sent since the last call.
+    fn take_frame(receiver: &mut receiver::Receiver) -> sender::Snapshot {
+        receiver
+            .wait_frame(Duration::ZERO)
+            .unwrap()
+            .expect("a frame was sent")
+    }
+
+    #[test]
+    fn the_retry_policy_decides_which_failures_are_thrown() {
+        let mut camera = camera_without_receiver("retry-policy", 1, 1);
+
+        // By default, connecting is retried while a malformed frame is thrown.
+        assert_eq!(camera.send(&[0; 4]).unwrap(), 0);
+        assert!(camera.send(&[0; 3]).is_err());
+
+        camera.set_retry_policy(RetryPolicy::Never);
+        let error = camera.send(&[0; 4]).unwrap_err();
+        assert!(error.reason.starts_with("INIT_NO_RECEIVER: "));
+
+        camera.set_retry_policy(RetryPolicy::Always);
+        assert_eq!(camera.send(&[0; 3]).unwrap(), 0);
+    }
+
+    #[test]
+    fn reconfigure_and_send_never_shows_a_frame_of_another_size() {
+        let (mut camera, mut receiver) = camera_with_receiver("reconfigure-and-send", 1, 1);
+
+        // Every frame is filled with its width, so a header and image of different frames
+        // would show up as a mismatch.
+        let reader = thread::spawn(move || {
+            for _ in 0..1000 {
+                let snapshot = receiver.snapshot().unwrap();
+
+                if let Ok(config) = snapshot.config {
+                    let width = config.width() as u8;
+                    assert_eq!(snapshot.image.len(), config.frame_len());
+                    assert!(snapshot.image.iter().all(|&value| value == width));
+                }
+            }
+        });
+
+        for i in 0..1000 {
+            let width = 1 + i % 3;
+            let frame = vec![width as u8; width as usize * 4];
+
+            camera.reconfigure_and_send(width, 1, &frame).unwrap();
+            assert_eq!(camera.config.width(), width);
+        }
+
+        reader.join().unwrap();
+
+        // A frame of the wrong size changes nothing.
+        let config = camera.config;
+        assert!(camera.reconfigure_and_send(4, 4, &[0; 4]).is_err());
+        assert_eq!(camera.config, config);
+    }
+
+    #[test]
+    fn is_running_follows_start_and_stop() {
+        let mut camera = Camera::new(1, 1, None).unwrap();
+        assert!(!camera.is_running());
+
+        camera.start();
+        assert!(camera.is_running());
+
+        camera.stop();
+        assert!(!camera.is_running());
+
+        // A camera kept warm isn't running while stopped either.
+        camera.keep_warm(true);
+        camera.start();
+        camera.stop();
+        assert!(!camera.is_running());
+
+        camera.start();
+        camera.dispose();
+        assert!(!camera.is_running());
+    }
+
+    #[test]
+    fn warm_cameras_resume_without_reconnecting() {
+        let (mut camera, mut receiver) = camera_with_receiver("keep-warm", 1, 1);
+        let want = receiver.names().want.clone();
+        camera.keep_warm(true);
+
+        camera.send(&[1; 4]).unwrap();
+        assert_eq!(take_frame(&mut receiver).image, [1; 4]);
+
+        camera.stop();
+        assert!(camera.send(&[2; 4]).is_err());
+        // `WANT` only exists while a sender holds it.
+        assert!(Event::exists(&want).unwrap());
+
+        camera.start();
+        assert!(matches!(
+            camera.connection_state(),
+            ConnectionState::Connected
+        ));
+
+        camera.send(&[3; 4]).unwrap();
+        assert_eq!(take_frame(&mut receiver).image, [3; 4]);
+
+        camera.stop();
+        camera.dispose();
+        assert!(!Event::exists(&want).unwrap());
+    }
+
+    #[test]
+    fn send_tasks_publish_and_report_errors() {
+        let (mut camera, mut receiver) = camera_with_receiver("send-async", 1, 1);
+
+        let mut task = camera.send_task(Buffer::from(vec![1; 4]));
+        task.compute().unwrap();
+        assert_eq!(take_frame(&mut receiver).image, [1; 4]);
+
+        camera.stop();
+
+        let mut task = camera.send_task(Buffer::from(vec![2; 4]));
+        let error = task.compute().unwrap_err();
+        assert_eq!(error.reason, "the camera isn't running");
+    }
+
+    #[test]
+    fn send_frame_describes_each_frame_by_its_own_size() {
+        let (mut camera, mut receiver) = camera_with_receiver("send-frame", 4, 4);
+        let config = camera.config;
+
+        camera.send_frame(&[1; 2 * 4], 2, 1).unwrap();
+        let frame = take_frame(&mut receiver);
+        let frame_config = frame.config.unwrap();
+        assert_eq!((frame_config.width(), frame_config.height()), (2, 1));
+        assert_eq!(frame.image, [1; 2 * 4]);
+
+        camera.send_frame(&[2; 3 * 2 * 4], 3, 2).unwrap();
+        let frame = take_frame(&mut receiver);
+        let frame_config = frame.config.unwrap();
+        assert_eq!((frame_config.width(), frame_config.height()), (3, 2));
+        assert_eq!(frame.image, [2; 3 * 2 * 4]);
+
+        assert_eq!(camera.config, config);
+    }
+
+    #[test]
+    fn send_frame_rejects_invalid_sizes() {
+        let (mut camera, _receiver) = camera_with_receiver("send-frame-invalid", 4, 4);
+
+        assert!(camera.send_frame(&[], 0, 1).is_err());
+        assert!(camera.send_frame(&[0; 4], MAX_WIDTH + 1, 1).is_err());
+        assert!(camera.send_frame(&[0; 4], 2, 1).is_err());
+    }
+
+    #[test]
+    fn probing_connects_without_sending_a_frame() {
+        let (mut camera, mut receiver) = camera_with_receiver("probe", 1, 1);
+
+        assert!(camera.probe().unwrap());
+        assert!(matches!(
+            camera.connection_state(),
+            ConnectionState::Connected
+        ));
+        assert!(receiver.wait_frame(Duration::ZERO).unwrap().is_none());
+        assert!(receiver.snapshot().unwrap().image.is_empty());
+    }
+
+    #[test]
+    fn probing_without_a_receiver_fails() {
+        let mut camera = camera_without_receiver("probe-missing", 1, 1);
+
+        // Retried by default, like a send.
+        assert!(!camera.probe().unwrap());
+
+        camera.set_retry_policy(RetryPolicy::Never);
+        let error = camera.probe().unwrap_err();
+        assert!(error.reason.starts_with("INIT_NO_RECEIVER: "));
+        assert!(!matches!(
+            camera.connection_state(),
+            ConnectionState::Connected
+        ));
+    }
+
+    #[test]
+    fn batches_report_how_many_frames_were_sent() {
+        let (mut camera, mut receiver) = camera_with_receiver("send-batch", 1, 1);
+        let frames = |values: &[u8]| {
+            values
+                .iter()
+                .map(|&value| Buffer::from(vec![value; 4]))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(camera.send_batch(frames(&[1, 2, 3])).unwrap(), 3);
+        assert_eq!(take_frame(&mut receiver).image, [3; 4]);
+
+        let mut batch = frames(&[4, 5]);
+        batch.insert(1, Buffer::from(vec![0; 3]));
+
+        let error = camera.send_batch(batch).unwrap_err();
+        assert!(error.reason.contains("sent 1 of 3 frames"));
+        assert_eq!(take_frame(&mut receiver).image, [4; 4]);
+    }
+
+    #[test]
+    fn send_errors_carry_their_source_chain_as_causes() {
+        use crate::win32::OpenMutexError;
+        use windows::Win32::Foundation::ERROR_ACCESS_DENIED;
+        use windows::core::HRESULT;
+
+        let os = windows::core::Error::from_hresult(HRESULT::from_win32(ERROR_ACCESS_DENIED.0));
+        let error = send_error(
+            sender::Error::Init {
+                source: sender::InitError::OpenMutex {
+                    source: OpenMutexError::Os {
+                        source: os,
+                        name: "mutex".to_owned(),
+                    },
+                },
+            },
+            Some("context".to_owned()),
+        );
+
+        assert!(error.reason.starts_with("INIT_OPEN_MUTEX: context: "));
+
+        let causes: Vec<&str> =
+            std::iter::successors(error.cause.as_deref(), |cause| cause.cause.as_deref())
+                .map(|cause| cause.reason.as_str())
+                .collect();
+
+        assert_eq!(causes.len(), 3);
+        assert_eq!(causes[0], "failed to open the mutex");
+        assert_eq!(causes[1], "failed to open an existing mutex (`mutex`)");
+        // The OS message is localized, but the code isn't.
+        assert!(causes[2].ends_with("(HRESULT 0x80070005)"));
+    }
+
+    #[test]
+    fn send_pointer_reads_the_frame_from_native_memory() {
+        let (mut camera, mut receiver) = camera_with_receiver("send-pointer", 2, 1);
+        let frame: Vec<u8> = (1..=8).collect();
+        let address = BigInt::from(frame.as_ptr() as u64);
+
+        assert_eq!(camera.send_pointer(address, 8).unwrap(), 8);
+        assert_eq!(take_frame(&mut receiver).image, frame);
+    }
+
+    #[test]
+    fn send_pointer_rejects_bad_addresses_and_lengths() {
+        let (mut camera, _receiver) = camera_with_receiver("send-pointer-invalid", 2, 1);
+        let frame = [0u8; 8];
+        let address = || BigInt::from(frame.as_ptr() as u64);
+
+        assert!(camera.send_pointer(BigInt::from(0u64), 8).is_err());
+        assert!(camera.send_pointer(BigInt::from(-1i64), 8).is_err());
+        assert!(camera.send_pointer(BigInt::from(u128::MAX), 8).is_err());
+        // Checked before the memory is read.
+        assert!(camera.send_pointer(address(), 4).is_err());
+        assert!(camera.send_pointer(address(), 16).is_err());
+    }
+
+    #[test]
+    fn send_with_options_overrides_one_frame_only() {
+        let (mut camera, mut receiver) = camera_with_receiver("send-with-options", 2, 2);
+        let config = camera.config;
+
+        camera
+            .send_with_options(
+                &[1; 4],
+                SendOptions {
+                    width: Some(1),
+                    height: Some(1),
+                    format: None,
+                    mirror: Some(MirrorMode::Horizontal),
+                    resize: None,
+                },
+            )
+            .unwrap();
+
+        assert_eq!(
+            take_frame(&mut receiver).config.unwrap(),
+            config
+                .resized(1, 1)
+                .unwrap()
+                .with_mirror_mode(sender::MirrorMode::Horizontal)
+        );
+        assert_eq!(camera.config, config);
+
+        camera.send(&[2; 16]).unwrap();
+        assert_eq!(take_frame(&mut receiver).config.unwrap(), config);
+    }
+
+    #[test]
+    fn send_returns_the_padded_frame_size() {
+        let (mut camera, mut receiver) = camera_with_receiver("send-count", 2, 2);
+
+        assert_eq!(camera.send(&[1; 16]).unwrap(), 16);
+
+        camera.set_pad_short_frames(true);
+        assert_eq!(camera.send(&[2; 4]).unwrap(), 16);
+        assert_eq!(take_frame(&mut receiver).image.len(), 16);
+
+        // The last frame was sent just now, so the next one is throttled and writes nothing.
+        camera.set_max_fps(Some(1));
+        assert_eq!(camera.send(&[3; 16]).unwrap(), 0);
+    }
+
+    #[test]
+    fn builders_configure_every_setting() {
+        let builder = CameraBuilder {
+            device_index: 2,
+            format: sender::PixelFormat::Fp16Linear,
+            mirror_mode: sender::MirrorMode::Horizontal,
+            resize_mode: sender::ResizeMode::Disabled,
+            frame_timeout_ms: Some(250),
+            ..CameraBuilder::new(640, 480)
+        };
+
+        let camera = builder.build().unwrap();
+
+        assert_eq!(
+            camera.config,
+            FrameConfig::new(640, 480, sender::PixelFormat::Fp16Linear)
+                .unwrap()
+                .with_mirror_mode(sender::MirrorMode::Horizontal)
+                .with_resize_mode(sender::ResizeMode::Disabled)
+                .with_frame_timeout_ms(250)
+                .unwrap()
+        );
+        assert_eq!(camera.options.device, device_names(2, None, None).unwrap());
+    }
+
+    #[test]
+    fn builders_reject_invalid_settings() {
+        let invalid = [
+            CameraBuilder::new(0, 480),
+            CameraBuilder {
+                device_index: MAX_DEVICES,
+                ..CameraBuilder::new(640, 480)
+            },
+            CameraBuilder {
+                frame_timeout_ms: Some(u32::MAX),
+                ..CameraBuilder::new(640, 480)
+            },
+        ];
+
+        for builder in invalid {
+            assert!(builder.build().is_err());
+        }
+    }
+
+    #[test]
+    fn custom_formats_take_one_to_255_bytes_per_pixel() {
+        let mut camera = Camera::new(640, 480, None).unwrap();
+
+        camera.set_custom_format(100, 2).unwrap();
+        assert_eq!(camera.config.row_len(), 640 * 2);
+
+        let config = camera.config;
+        assert!(camera.set_custom_format(100, 0).is_err());
+        assert!(camera.set_custom_format(100, 256).is_err());
+        assert_eq!(camera.config, config);
+    }
+
+    #[test]
+    fn max_frame_bytes_is_the_image_region_in_whole_pixels() {
+        assert_eq!(
+            Camera::max_frame_bytes(PixelFormat::Uint8) as usize,
+            sender::MAX_IMAGE_SIZE
+        );
+
+        for format in [PixelFormat::Fp16Gamma, PixelFormat::Fp16Linear] {
+            assert_eq!(
+                Camera::max_frame_bytes(format) as usize,
+                sender::MAX_IMAGE_SIZE
+            );
+        }
+    }
+
+    #[test]
+    fn max_frame_bytes_fits_a_4k_frame_in_every_format() {
+        for (format, bytes_per_pixel) in [
+            (PixelFormat::Uint8, 4),
+            (PixelFormat::Fp16Gamma, 8),
+            (PixelFormat::Fp16Linear, 8),
+        ] {
+            let max = Camera::max_frame_bytes(format) as usize;
+
+            assert_eq!(max % bytes_per_pixel, 0);
+            assert!(3840 * 2160 * bytes_per_pixel <= max);
+        }
+    }
+
+    #[test]
+    fn frames_sent_in_place_are_written_to_the_shared_memory() {
+        let (mut camera, mut receiver) = camera_with_receiver("send-in-place", 2, 2);
+        let pattern: Vec<u8> = (0..16).collect();
+
+        let published = camera
+            .send_in_place(|data| {
+                assert_eq!(data.len(), pattern.len());
+                data.copy_from_slice(&pattern);
+                Ok(())
+            })
+            .unwrap();
+
+        assert!(published);
+
+        let frame = take_frame(&mut receiver);
+        assert_eq!(frame.image, pattern);
+        assert_eq!(frame.config.unwrap(), camera.config);
+    }
+
+    #[test]
+    fn frames_sent_in_place_are_published_before_the_error() {
+        let (mut camera, mut receiver) = camera_with_receiver("send-in-place-error", 1, 1);
+
+        let result = camera.send_in_place(|data| {
+            data.copy_from_slice(&[1, 2, 3, 4]);
+            Err(napi::Error::from_reason("callback failed"))
+        });
+
+        assert_eq!(result.unwrap_err().reason, "callback failed");
+        assert_eq!(take_frame(&mut receiver).image, [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn send_bgr_writes_rgba_to_the_shared_memory() {
+        let (mut camera, mut receiver) = camera_with_receiver("send-bgr", 2, 1);
+
+        camera
+            .send_bgr(&[0x10, 0x20, 0x30, 0x40, 0x50, 0x60, 0x70, 0x80], 4)
+            .unwrap();
+
+        let frame = take_frame(&mut receiver);
+        assert_eq!(
+            frame.image,
+            [0x30, 0x20, 0x10, 0x40, 0x70, 0x60, 0x50, 0x80]
+        );
+        assert_eq!(
+            frame.config.unwrap(),
+            camera.config.with_format(sender::PixelFormat::Uint8)
+        );
+
+        camera.send_bgr(&[1, 2, 3, 4, 5, 6], 3).unwrap();
+        assert_eq!(
+            take_frame(&mut receiver).image,
+            [3, 2, 1, 0xff, 6, 5, 4, 0xff]
+        );
+
+        assert!(camera.send_bgr(&[0; 8], 2).is_err());
+        assert!(camera.send_bgr(&[0; 7], 4).is_err());
+    }
+
+    #[test]
+    fn lenient_resize_keeps_the_previous_config() {
+        let mut camera = Camera::new(1280, 720, None).unwrap();
+        camera.set_lenient_resize(true);
+        let config = camera.config;
+
+        camera.resize(0, 720).unwrap();
+        camera.resize(1280, u32::MAX).unwrap();
+
+        assert_eq!(camera.config, config);
+        assert!(camera.config.is_valid());
+    }
+
+    #[test]
+    fn strict_resize_rejects_invalid_sizes() {
+        let mut camera = Camera::new(1280, 720, None).unwrap();
+        let config = camera.config;
+
+        assert!(camera.resize(0, 720).is_err());
+        assert_eq!(camera.config, config);
+
+        camera.resize(640, 480).unwrap();
+        assert_eq!((camera.width(), camera.height()), (640, 480));
+    }
+
+    #[test]
+    fn device_indices_out_of_range_are_rejected() {
+        let max = sender::MAX_DEVICES;
+        assert!(Camera::new(1280, 720, Some(max)).is_err());
+        assert!(Camera::is_driver_available(Some(max)).is_err());
+
+        let mut camera = Camera::new(1280, 720, Some(max - 1)).unwrap();
+        let device = camera.options.device.clone();
+
+        assert!(camera.set_device(max, None, None).is_err());
+        assert_eq!(camera.options.device, device);
+
+        let options = ReconfigureOptions {
+            width: Some(640),
+            device_index: Some(max),
+            ..ReconfigureOptions::default()
+        };
+
+        assert!(camera.reconfigure(options).is_err());
+        assert_eq!(camera.width(), 1280);
+        assert_eq!(camera.options.device, device);
+    }
+
+    #[test]
+    fn send_fp16_writes_the_values_as_is() {
+        let (mut camera, mut receiver) = camera_with_receiver("send-fp16", 2, 1);
+        // 1.0, 0.5, 0.0 and -2.0 as half floats, then a pixel of arbitrary bit patterns.
+        let values = [
+            0x3c00, 0x3800, 0x0000, 0xc000, 0x0001, 0x7bff, 0x8000, 0xffff,
+        ];
+
+        camera.send_fp16(Uint16Array::new(values.to_vec())).unwrap();
+
+        let frame = take_frame(&mut receiver);
+        let bytes: Vec<u8> = values
+            .iter()
+            .flat_map(|value| value.to_ne_bytes())
+            .collect();
+        assert_eq!(frame.image, bytes);
+        // UnityCapture's code for linear half floats.
+        assert_eq!(frame.header.format, 2);
+        assert_eq!(
+            frame.config.unwrap(),
+            camera.config.with_format(sender::PixelFormat::Fp16Linear)
+        );
+
+        assert!(camera.send_fp16(Uint16Array::new(vec![0; 7])).is_err());
+    }
+
+    #[test]
+    fn reconfigure_rejects_object_names_without_a_device_index() {
+        let mut camera = Camera::new(1280, 720, None).unwrap();
+        let config = camera.config;
+
+        let options = ReconfigureOptions {
+            width: Some(640),
+            base: Some("vcam".to_owned()),
+            ..ReconfigureOptions::default()
+        };
+
+        assert!(camera.reconfigure(options).is_err());
+        assert_eq!(camera.config, config);
     }
 }
@@ -0,0 +1,155 @@
+use crate::sender::{DeviceNames, HEADER_SIZE, Header, MAX_IMAGE_SIZE, Snapshot};
+use crate::win32::{
+    CreateEventError, CreateFileMappingError, CreateMutexError, Event, FileMapping, LockMutexError,
+    Mutex, OpenEventError, ReadWrite, SetEventError, SharedMemory, WaitEventError,
+};
+use snafu::{ResultExt, Snafu};
+use std::time::Duration;
+
+#[derive(Debug, Snafu)]
+#[snafu(module)]
+pub enum CreateReceiverError {
+    #[snafu(display("failed to create the mutex"))]
+    CreateMutex { source: CreateMutexError },
+
+    #[snafu(display("failed to create the `SENT` event"))]
+    CreateSentEvent { source: CreateEventError },
+
+    #[snafu(display("failed to create the shared memory"))]
+    CreateSharedMemory { source: CreateFileMappingError },
+
+    #[snafu(display("failed to lock the mutex"))]
+    LockMutex { source: LockMutexError },
+}
+
+#[derive(Debug, Snafu)]
+#[snafu(module)]
+pub enum ReceiveError {
+    #[snafu(display("failed to open the `WANT` event (has a sender connected?)"))]
+    OpenWantEvent { source: OpenEventError },
+
+    #[snafu(display("failed to signal (set) the `WANT` event"))]
+    SignalWant { source: SetEventError },
+
+    #[snafu(display("failed to wait for the `SENT` event"))]
+    WaitSent { source: WaitEventError },
+
+    #[snafu(display("failed to lock the mutex"))]
+    LockMutex { source: LockMutexError },
+}
+
+/// The receiving end of a device, doing what the driver does: it creates the mutex, the `SENT`
+/// event and the shared memory, and reads the frames senders publish.
+///
+/// Senders create `WANT`, so frames can only be requested once one has connected.
+#[derive(Debug)]
+pub struct Receiver {
+    names: DeviceNames,
+    sent_frame: Event,
+    shared: SharedMemory,
+}
+
+impl Receiver {
+    /// Creates the device's shared objects and writes the header's `max_size`. Fails if the
+    /// shared memory exists already, e.g. because the driver (or another receiver) is using
+    /// the device.
+    pub fn create(names: DeviceNames) -> Result<Receiver, CreateReceiverError> {
+        let mutex =
+            Mutex::create_new(&names.mutex).context(create_receiver_error::CreateMutexSnafu)?;
+
+        // Only the shared memory has to be new: the mutex and the event are harmless to share.
+        let (sent_frame, _) =
+            Event::create_new(&names.sent).context(create_receiver_error::CreateSentEventSnafu)?;
+
+        let mapping =
+            FileMapping::<ReadWrite>::create_new(&names.data, HEADER_SIZE + MAX_IMAGE_SIZE)
+                .context(create_receiver_error::CreateSharedMemorySnafu)?;
+
+        // SAFETY: the mapping is only accessed while holding the mutex.
+        let mut shared = unsafe { SharedMemory::new(mapping, mutex) };
+
+        shared
+            .with(Duration::MAX, |bytes, _| {
+                bytes[..HEADER_SIZE].copy_from_slice(Header::new().as_bytes());
+            })
+            .context(create_receiver_error::LockMutexSnafu)?;
+
+        Ok(Receiver {
+            names,
+            sent_frame,
+            shared,
+        })
+    }
+
+    pub fn names(&self) -> &DeviceNames {
+        &self.names
+    }
+
+    /// Asks the connected sender for a frame by signaling `WANT`.
+    pub fn request_frame(&self) -> Result<(), ReceiveError> {
+        Event::open_existing(&self.names.want)
+            .context(receive_error::OpenWantEventSnafu)?
+            .set()
+            .context(receive_error::SignalWantSnafu)
+    }
+
+    /// Waits up to `timeout` for a sender to signal `SENT`, then reads the frame. Returns `None`
+    /// if no frame arrived in time.
+    pub fn wait_frame(&mut self, timeout: Duration) -> Result<Option<Snapshot>, ReceiveError> {
+        match self.sent_frame.wait_timeout(timeout) {
+            Ok(()) => self.snapshot().map(Some),
+            Err(WaitEventError::Timeout) => Ok(None),
+            Err(e) => Err(e).context(receive_error::WaitSentSnafu),
+        }
+    }
+
+    /// Reads the current header and frame, whether or not a new one has been signaled.
+    pub fn snapshot(&mut self) -> Result<Snapshot, ReceiveError> {
+        self.shared
+            .with(Duration::MAX, |bytes, _| Snapshot::read(bytes))
+            .context(receive_error::LockMutexSnafu)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_device(name: &str) -> DeviceNames {
+        let base = format!("node-vcam-test-{}-{name}", std::process::id());
+        DeviceNames::for_device(&base, 0)
+    }
+
+    #[test]
+    fn a_device_has_one_receiver() {
+        let _first = Receiver::create(test_device("second-receiver")).unwrap();
+
+        assert!(matches!(
+            Receiver::create(test_device("second-receiver")),
+            Err(CreateReceiverError::CreateSharedMemory {
+                source: CreateFileMappingError::AlreadyExists { .. }
+            })
+        ));
+    }
+
+    #[test]
+    fn frames_cant_be_requested_before_a_sender_connects() {
+        let receiver = Receiver::create(test_device("no-sender")).unwrap();
+
+        assert!(matches!(
+            receiver.request_frame(),
+            Err(ReceiveError::OpenWantEvent { .. })
+        ));
+    }
+
+    #[test]
+    fn a_new_receiver_has_no_frame() {
+        let mut receiver = Receiver::create(test_device("no-frame")).unwrap();
+
+        assert!(receiver.wait_frame(Duration::ZERO).unwrap().is_none());
+
+        let snapshot = receiver.snapshot().unwrap();
+        assert_eq!(snapshot.header.max_size as usize, MAX_IMAGE_SIZE);
+        assert!(snapshot.image.is_empty());
+    }
+}
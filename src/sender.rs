@@ -1,69 +1,517 @@
 use crate::{
     utils::OptionExt,
     win32::{
-        CreateEventError, Event, FileMapping, LockMutexError, Mutex, OpenEventError,
-        OpenFileMappingError, OpenMutexError, SetEventError, SharedMemory, WaitEventError,
+        CloseError, CreateEventError, Event, ExistsError, FileMapping, LockMutexError, Mutex,
+        OpenEventError, OpenFileMappingError, OpenMutexError, OpenPipeError, Pipe, ResetEventError,
+        SetEventError, SharedMemory, WaitEventError, WritePipeError, win32_error_code,
     },
 };
-use snafu::{ResultExt, Snafu};
+use snafu::{ResultExt, Snafu, ensure};
 use std::ffi::c_int;
+use std::num::{NonZeroU8, NonZeroU32};
+use std::slice;
+use std::thread;
+use std::time::{Duration, Instant};
 
-const MAX_IMAGE_SIZE: usize = 3840 * 2160 * 4 * size_of::<u16>();
+pub const MAX_IMAGE_SIZE: usize = 3840 * 2160 * 4 * size_of::<u16>();
+pub const HEADER_SIZE: usize = size_of::<Header>();
+
+/// The common prefix of all the shared object names.
+pub const OBJECT_NAME_BASE: &str = "UnityCapture";
+
+/// The names of a receiver's shared objects.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceNames {
+    pub mutex: String,
+    pub want: String,
+    pub sent: String,
+    pub data: String,
+}
+
+impl DeviceNames {
+    /// Returns the names used by the device with the given index, where 0 is the first one.
+    ///
+    /// The first device's names are `{base}_Mutx` etc.; later devices append their number,
+    /// e.g. `{base}_Mutx2` for index 1.
+    pub fn for_device(base: &str, index: u32) -> DeviceNames {
+        let number = match index {
+            0 => String::new(),
+            index => (u64::from(index) + 1).to_string(),
+        };
+
+        let name = |suffix: &str| format!("{base}_{suffix}{number}");
+
+        DeviceNames {
+            mutex: name("Mutx"),
+            want: name("Want"),
+            sent: name("Sent"),
+            data: name("Data"),
+        }
+    }
+
+    /// Like [`DeviceNames::for_device`], but fails unless `index` is below [`MAX_DEVICES`].
+    pub fn try_for_device(base: &str, index: u32) -> Result<DeviceNames, DeviceIndexError> {
+        ensure!(index < MAX_DEVICES, DeviceIndexSnafu { index });
+        Ok(DeviceNames::for_device(base, index))
+    }
+
+    /// Returns the names qualified with a kernel object namespace, e.g. `Global\`.
+    pub fn with_prefix(self, prefix: &str) -> DeviceNames {
+        let name = |name: String| format!("{prefix}{name}");
+
+        DeviceNames {
+            mutex: name(self.mutex),
+            want: name(self.want),
+            sent: name(self.sent),
+            data: name(self.data),
+        }
+    }
+}
+
+impl Default for DeviceNames {
+    fn default() -> Self {
+        DeviceNames::for_device(OBJECT_NAME_BASE, 0)
+    }
+}
+
+#[derive(Debug, Snafu)]
+#[snafu(display("the device index ({index}) must be less than {MAX_DEVICES}"))]
+pub struct DeviceIndexError {
+    index: u32,
+}
+
+/// Checks whether the receiver's shared objects exist, i.e. whether the driver is
+/// installed and running.
+pub fn is_driver_available(names: &DeviceNames) -> Result<bool, ExistsError> {
+    Ok(Mutex::exists(&names.mutex)?
+        && Event::exists(&names.sent)?
+        && FileMapping::exists(&names.data)?)
+}
+
+/// How many device slots [`available_devices`] checks by default. UnityCapture's installer
+/// registers at most this many devices.
+pub const MAX_DEVICES: u32 = 10;
+
+/// Returns the indices, below `count`, of the devices whose receiver is available (see
+/// [`is_driver_available`]).
+pub fn available_devices(base: &str, count: u32) -> Result<Vec<u32>, ExistsError> {
+    let mut available = Vec::new();
+
+    for index in 0..count {
+        if is_driver_available(&DeviceNames::for_device(base, index))? {
+            available.push(index);
+        }
+    }
+
+    Ok(available)
+}
+
+/// Uint8 frames are RGBA, four bytes per pixel.
+pub const BYTES_PER_PIXEL: usize = 4;
+
+/// How long the receiver keeps showing the last frame when no new one arrives, by default.
+/// Effectively forever.
+pub const DEFAULT_FRAME_TIMEOUT_MS: u32 = c_int::MAX as u32 - 200;
 
 pub const MAX_WIDTH: u32 = c_int::MAX as u32;
 pub const MAX_HEIGHT: u32 = c_int::MAX as u32;
 
-#[derive(Debug, Copy, Clone)]
+/// The pixel formats the receiver accepts. Every format but `Custom` is RGBA.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum PixelFormat {
+    /// 8 bits per channel.
+    #[default]
+    Uint8,
+    /// 16-bit floats per channel, gamma-encoded.
+    Fp16Gamma,
+    /// 16-bit floats per channel, linear.
+    Fp16Linear,
+    /// A format UnityCapture doesn't know, for custom receivers: `code` is written to the
+    /// header as is, and each pixel takes `bytes_per_pixel` bytes.
+    Custom {
+        code: c_int,
+        bytes_per_pixel: NonZeroU8,
+    },
+}
+
+impl PixelFormat {
+    /// Returns the value the header's `format` field holds for this format.
+    fn code(self) -> c_int {
+        match self {
+            PixelFormat::Uint8 => 0,
+            PixelFormat::Fp16Gamma => 1,
+            PixelFormat::Fp16Linear => 2,
+            PixelFormat::Custom { code, .. } => code,
+        }
+    }
+
+    /// Returns the standard format with the given code. Custom formats can't be recognized,
+    /// since their size isn't recorded in the header.
+    fn from_code(code: c_int) -> Option<PixelFormat> {
+        [
+            PixelFormat::Uint8,
+            PixelFormat::Fp16Gamma,
+            PixelFormat::Fp16Linear,
+        ]
+        .into_iter()
+        .find(|format| format.code() == code)
+    }
+
+    pub fn bytes_per_pixel(self) -> usize {
+        match self {
+            PixelFormat::Uint8 => BYTES_PER_PIXEL,
+            PixelFormat::Fp16Gamma | PixelFormat::Fp16Linear => 4 * size_of::<u16>(),
+            PixelFormat::Custom {
+                bytes_per_pixel, ..
+            } => bytes_per_pixel.get().into(),
+        }
+    }
+}
+
+/// Whether the receiver mirrors the image.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum MirrorMode {
+    #[default]
+    Disabled,
+    Horizontal,
+}
+
+impl MirrorMode {
+    /// Returns the value the header's `mirror_mode` field holds for this mode.
+    fn code(self) -> c_int {
+        match self {
+            MirrorMode::Disabled => 0,
+            MirrorMode::Horizontal => 1,
+        }
+    }
+
+    /// Returns the mode with the given code.
+    fn from_code(code: c_int) -> Option<MirrorMode> {
+        [MirrorMode::Disabled, MirrorMode::Horizontal]
+            .into_iter()
+            .find(|mode| mode.code() == code)
+    }
+}
+
+/// Whether the receiver resizes frames that don't match the resolution it negotiated.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum ResizeMode {
+    /// Frames are shown at their own size.
+    Disabled,
+    /// Frames are scaled with linear interpolation.
+    #[default]
+    Linear,
+}
+
+impl ResizeMode {
+    /// Returns the value the header's `resize_mode` field holds for this mode.
+    fn code(self) -> c_int {
+        match self {
+            ResizeMode::Disabled => 0,
+            ResizeMode::Linear => 1,
+        }
+    }
+
+    /// Returns the mode with the given code.
+    fn from_code(code: c_int) -> Option<ResizeMode> {
+        [ResizeMode::Disabled, ResizeMode::Linear]
+            .into_iter()
+            .find(|mode| mode.code() == code)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct FrameConfig {
     width: u32,
     height: u32,
+    format: PixelFormat,
+    mirror_mode: MirrorMode,
+    resize_mode: ResizeMode,
+    // Fits in a `c_int`.
+    frame_timeout_ms: u32,
+    // Never sent to the receiver; only throttles the sender.
+    max_fps: Option<NonZeroU32>,
 }
 
 impl FrameConfig {
-    pub fn new(width: u32, height: u32) -> FrameConfig {
-        if width > c_int::MAX as u32 {
-            panic!("`width` must not exceed {}", MAX_WIDTH);
+    /// Fails if either dimension is zero, `width` exceeds [`MAX_WIDTH`] or `height` exceeds
+    /// [`MAX_HEIGHT`].
+    pub fn new(
+        width: u32,
+        height: u32,
+        format: PixelFormat,
+    ) -> Result<FrameConfig, InvalidDimensionsError> {
+        ensure!(
+            width != 0 && height != 0,
+            invalid_dimensions_error::ZeroSnafu { width, height }
+        );
+
+        ensure!(
+            width <= MAX_WIDTH && height <= MAX_HEIGHT,
+            invalid_dimensions_error::TooLargeSnafu { width, height }
+        );
+
+        Ok(Self {
+            width,
+            height,
+            format,
+            mirror_mode: MirrorMode::Disabled,
+            resize_mode: ResizeMode::Linear,
+            frame_timeout_ms: DEFAULT_FRAME_TIMEOUT_MS,
+            max_fps: None,
+        })
+    }
+
+    /// Returns a copy of `self` with the given dimensions instead, or `None` if they aren't
+    /// valid.
+    pub fn resized(self, width: u32, height: u32) -> Option<FrameConfig> {
+        Some(Self {
+            width,
+            height,
+            ..self
+        })
+        .filter(FrameConfig::is_valid)
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns a copy of `self` with `format` instead.
+    pub fn with_format(self, format: PixelFormat) -> FrameConfig {
+        Self { format, ..self }
+    }
+
+    /// Returns a copy of `self` with the frame timeout set to `timeout_ms`, or `None` if it
+    /// exceeds `c_int::MAX`.
+    pub fn with_frame_timeout_ms(self, timeout_ms: u32) -> Option<FrameConfig> {
+        (timeout_ms <= c_int::MAX as u32).then_some(Self {
+            frame_timeout_ms: timeout_ms,
+            ..self
+        })
+    }
+
+    /// Returns a copy of `self` publishing at most `max_fps` frames per second, or without a
+    /// limit if `None`.
+    pub fn with_max_fps(self, max_fps: Option<NonZeroU32>) -> FrameConfig {
+        Self { max_fps, ..self }
+    }
+
+    /// Returns the shortest time allowed between two published frames.
+    pub fn min_frame_interval(&self) -> Duration {
+        self.max_fps
+            .map_or(Duration::ZERO, |fps| Duration::from_secs(1) / fps.get())
+    }
+
+    pub fn resize_mode(&self) -> ResizeMode {
+        self.resize_mode
+    }
+
+    /// Returns a copy of `self` with `resize_mode` instead.
+    pub fn with_resize_mode(self, resize_mode: ResizeMode) -> FrameConfig {
+        Self {
+            resize_mode,
+            ..self
         }
+    }
 
-        if height > c_int::MAX as u32 {
-            panic!("`height` must not exceed {}", MAX_HEIGHT);
+    /// Returns a copy of `self` with `mirror_mode` instead.
+    pub fn with_mirror_mode(self, mirror_mode: MirrorMode) -> FrameConfig {
+        Self {
+            mirror_mode,
+            ..self
         }
+    }
+
+    /// Returns the size of a single row in bytes.
+    pub fn row_len(&self) -> usize {
+        (self.width as usize).saturating_mul(self.format.bytes_per_pixel())
+    }
 
-        Self { width, height }
+    /// Returns the size of a frame in bytes, which may exceed `MAX_IMAGE_SIZE` (see
+    /// [`check_frame_size`]).
+    pub fn frame_len(&self) -> usize {
+        self.row_len().saturating_mul(self.height as usize)
     }
+
+    /// Returns `true` if the dimensions are non-zero and within the supported limits.
+    pub fn is_valid(&self) -> bool {
+        (1..=MAX_WIDTH).contains(&self.width) && (1..=MAX_HEIGHT).contains(&self.height)
+    }
+}
+
+#[derive(Debug, Snafu)]
+#[snafu(module)]
+pub enum InvalidDimensionsError {
+    #[snafu(display("the frame size ({width}x{height}) must be non-zero"))]
+    Zero { width: u32, height: u32 },
+
+    #[snafu(display(
+        "the frame size ({width}x{height}) exceeds the maximum ({MAX_WIDTH}x{MAX_HEIGHT})"
+    ))]
+    TooLarge { width: u32, height: u32 },
 }
 
+/// The header preceding the image in the shared memory region.
+///
+/// Only the sender writes it; the driver reads it but never writes anything back, so it has no
+/// way of advertising a preferred format and the format chosen here is authoritative.
 #[repr(C)]
-struct Header {
-    max_size: u32,
-    width: c_int,
-    height: c_int,
-    stride: c_int,
-    format: c_int,
-    resize_mode: c_int,
-    mirror_mode: c_int,
-    timeout: c_int,
+#[derive(Debug, Copy, Clone)]
+pub struct Header {
+    pub max_size: u32,
+    pub width: c_int,
+    pub height: c_int,
+    /// The distance between rows in pixels, not bytes: the receiver multiplies it by the
+    /// format's pixel size itself.
+    pub stride: c_int,
+    pub format: c_int,
+    pub resize_mode: c_int,
+    pub mirror_mode: c_int,
+    pub timeout: c_int,
 }
 
 impl Header {
-    fn fill(&mut self, width: c_int, height: c_int) {
-        const FORMAT_UINT8: c_int = 0;
-        const RESIZE_MODE_LINEAR: c_int = 1;
-        const MIRROR_MODE_DISABLED: c_int = 0;
-        const FRAME_TIMEOUT: c_int = c_int::MAX - 200;
+    pub(crate) fn new() -> Header {
+        Header {
+            max_size: MAX_IMAGE_SIZE as u32,
+            width: 0,
+            height: 0,
+            stride: 0,
+            format: 0,
+            resize_mode: 0,
+            mirror_mode: 0,
+            timeout: 0,
+        }
+    }
+
+    pub(crate) fn as_bytes(&self) -> &[u8] {
+        // SAFETY:
+        // - `Header` is `repr(C)` and consists of 4-byte integers only, so it has no padding.
+        // - `self` is valid for reads of `size_of::<Header>()` bytes.
+        unsafe { slice::from_raw_parts((self as *const Header).cast(), size_of::<Header>()) }
+    }
+
+    /// Reads a header from the start of `bytes`, if it is long enough to hold one.
+    fn read(bytes: &[u8]) -> Option<Header> {
+        let bytes = bytes.get(..size_of::<Header>())?;
+
+        // SAFETY:
+        // - `bytes` is valid for reads of `size_of::<Header>()` bytes.
+        // - `read_unaligned` has no alignment requirements.
+        // - `Header` can hold arbitrary bit patterns.
+        Some(unsafe { bytes.as_ptr().cast::<Header>().read_unaligned() })
+    }
+
+    /// Returns the frame config this header describes, or why it isn't one `fill` could have
+    /// written. Every field is checked.
+    fn config(&self) -> Result<FrameConfig, InvalidHeaderError> {
+        ensure!(
+            self.max_size as usize == MAX_IMAGE_SIZE,
+            invalid_header_error::MaxSizeSnafu {
+                max_size: self.max_size
+            }
+        );
+
+        // Negative dimensions wrap around to ones over the maximum.
+        let Some(format) = PixelFormat::from_code(self.format) else {
+            return invalid_header_error::FormatSnafu { code: self.format }.fail();
+        };
+
+        let config = FrameConfig::new(self.width as u32, self.height as u32, format)
+            .context(invalid_header_error::DimensionsSnafu)?;
+
+        ensure!(
+            self.stride == self.width,
+            invalid_header_error::StrideSnafu {
+                stride: self.stride,
+                width: self.width,
+            }
+        );
+
+        let Some(resize_mode) = ResizeMode::from_code(self.resize_mode) else {
+            return invalid_header_error::ResizeModeSnafu {
+                code: self.resize_mode,
+            }
+            .fail();
+        };
+
+        let Some(mirror_mode) = MirrorMode::from_code(self.mirror_mode) else {
+            return invalid_header_error::MirrorModeSnafu {
+                code: self.mirror_mode,
+            }
+            .fail();
+        };
+
+        // `c_int::MAX` fits, so only a negative timeout fails.
+        let Some(config) = u32::try_from(self.timeout)
+            .ok()
+            .and_then(|timeout_ms| config.with_frame_timeout_ms(timeout_ms))
+        else {
+            return invalid_header_error::TimeoutSnafu {
+                timeout: self.timeout,
+            }
+            .fail();
+        };
+
+        Ok(config
+            .with_resize_mode(resize_mode)
+            .with_mirror_mode(mirror_mode))
+    }
 
-        assert_eq!(self.max_size as usize, MAX_IMAGE_SIZE);
+    /// Writes everything but `max_size`, which belongs to the receiver and is checked at
+    /// connect (see `Uninit::try_init`).
+    ///
+    /// Fails, leaving the header untouched, if `stride` rows of `height` pixels wouldn't fit in
+    /// the image region.
+    fn fill(&mut self, config: FrameConfig) -> Result<(), SendFrameError> {
+        // The stride is the width, so this is exactly the region the header describes.
+        check_frame_size(&config)?;
 
-        self.width = width;
-        self.height = height;
-        self.stride = width;
-        self.format = FORMAT_UINT8;
-        self.resize_mode = RESIZE_MODE_LINEAR;
-        self.mirror_mode = MIRROR_MODE_DISABLED;
-        self.timeout = FRAME_TIMEOUT;
+        // `FrameConfig` guarantees that the dimensions and the timeout fit.
+        self.width = config.width as c_int;
+        self.height = config.height as c_int;
+        // Rows are tightly packed, whatever the format.
+        self.stride = config.width as c_int;
+        self.format = config.format.code();
+        self.resize_mode = config.resize_mode.code();
+        self.mirror_mode = config.mirror_mode.code();
+        self.timeout = config.frame_timeout_ms as c_int;
+
+        Ok(())
     }
 }
 
+/// Why a header isn't one [`Header::fill`] could have written.
+#[derive(Debug, Snafu)]
+#[snafu(module)]
+pub enum InvalidHeaderError {
+    #[snafu(display("the max size ({max_size}) isn't {MAX_IMAGE_SIZE}"))]
+    MaxSize { max_size: u32 },
+
+    #[snafu(display("the dimensions are invalid"))]
+    Dimensions { source: InvalidDimensionsError },
+
+    #[snafu(display("the stride ({stride}) isn't the width ({width})"))]
+    Stride { stride: c_int, width: c_int },
+
+    #[snafu(display("the format ({code}) is unknown"))]
+    Format { code: c_int },
+
+    #[snafu(display("the resize mode ({code}) is unknown"))]
+    ResizeMode { code: c_int },
+
+    #[snafu(display("the mirror mode ({code}) is unknown"))]
+    MirrorMode { code: c_int },
+
+    #[snafu(display("the timeout ({timeout}) is negative"))]
+    Timeout { timeout: c_int },
+}
+
 #[derive(Debug, Snafu)]
 pub enum Error {
     #[snafu(display("failed to initialize the sender"))]
@@ -74,9 +522,19 @@ pub enum Error {
 }
 
 impl Error {
+    /// Returns a stable code identifying the kind of error. Codes of errors raised while
+    /// connecting start with `INIT_`, the others with `SEND_`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::Init { source } => source.code(),
+            Error::Send { source } => source.code(),
+        }
+    }
+
+    /// Returns `true` if the error is likely transient, so sending again later may succeed.
     pub fn should_retry(&self) -> bool {
         match &self {
-            Error::Init { .. } => true,
+            Error::Init { source } => source.is_transient(),
             Error::Send { .. } => false,
         }
     }
@@ -99,6 +557,79 @@ pub enum InitError {
 
     #[snafu(display("failed to open the shared memory"))]
     OpenSharedMemory { source: OpenFileMappingError },
+
+    #[snafu(display(
+        "the shared memory was created by an incompatible receiver (max size {found}, expected {expected})"
+    ))]
+    IncompatibleMapping { found: u32, expected: usize },
+
+    #[snafu(display("failed to open the fallback pipe (shared memory: {shared_memory})"))]
+    OpenPipe {
+        source: OpenPipeError,
+        shared_memory: Box<InitError>,
+    },
+
+    #[snafu(display("failed to connect after {attempts} attempts"))]
+    Retried {
+        #[snafu(source(from(InitError, Box::new)))]
+        source: Box<InitError>,
+        attempts: u32,
+    },
+}
+
+impl InitError {
+    fn code(&self) -> &'static str {
+        match self {
+            InitError::OpenMutex {
+                source: OpenMutexError::NotFound { .. },
+            } => "INIT_NO_RECEIVER",
+            InitError::OpenMutex { .. } => "INIT_OPEN_MUTEX",
+            InitError::LockMutex {
+                source: LockMutexError::Timeout,
+            } => "INIT_LOCK_TIMEOUT",
+            InitError::LockMutex { .. } => "INIT_LOCK_MUTEX",
+            InitError::CreateWantEvent { .. } => "INIT_CREATE_WANT",
+            InitError::OpenSentEvent { .. } => "INIT_OPEN_SENT",
+            InitError::OpenSharedMemory { .. } => "INIT_OPEN_SHARED_MEMORY",
+            InitError::IncompatibleMapping { .. } => "INIT_INCOMPATIBLE_MAPPING",
+            InitError::OpenPipe { .. } => "INIT_OPEN_PIPE",
+            InitError::Retried { source, .. } => source.code(),
+        }
+    }
+
+    /// Returns `true` unless connecting can't succeed without outside intervention.
+    ///
+    /// A missing mutex (`ERROR_FILE_NOT_FOUND`) is retried: the receiver creates the shared
+    /// objects only once a program opens the camera, so a filter that isn't installed can't be
+    /// told from one nobody is using yet. Any other failure to open the mutex, such as being
+    /// denied access to it, won't go away by itself.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            InitError::OpenMutex {
+                source: OpenMutexError::NotFound { .. },
+            } => true,
+            InitError::OpenMutex { .. } => false,
+            InitError::Retried { source, .. } => source.is_transient(),
+            _ => true,
+        }
+    }
+
+    /// Returns `true` if the objects kept from the failed attempt can't be trusted anymore, or
+    /// the mutex can't be opened for another reason than not existing (yet), e.g. access
+    /// being denied. Retrying won't help then, so the next attempt should start from scratch.
+    pub fn is_terminal(&self) -> bool {
+        match self {
+            InitError::LockMutex {
+                source: LockMutexError::Os { .. },
+            }
+            | InitError::OpenMutex {
+                source: OpenMutexError::Os { .. },
+            } => true,
+            InitError::OpenPipe { shared_memory, .. } => shared_memory.is_terminal(),
+            InitError::Retried { source, .. } => source.is_terminal(),
+            _ => false,
+        }
+    }
 }
 
 #[derive(Debug, Snafu)]
@@ -110,8 +641,233 @@ pub enum SendFrameError {
     #[snafu(display("failed to lock the mutex"))]
     LockMutex { source: LockMutexError },
 
+    #[snafu(display("failed to reset the `WANT` event"))]
+    ResetWant { source: ResetEventError },
+
     #[snafu(display("failed to signal (set) the `SENT` event"))]
     SignalSent { source: SetEventError },
+
+    #[snafu(display("failed to reset the `SENT` event"))]
+    ResetSent { source: ResetEventError },
+
+    #[snafu(display("failed to write the frame to the pipe"))]
+    WritePipe { source: WritePipeError },
+
+    #[snafu(display("failed to wait for the completion event"))]
+    WaitCompletion { source: WaitEventError },
+
+    #[snafu(display("no frame has been sent yet"))]
+    NoImage,
+
+    #[snafu(display("the mutex was abandoned and the header is corrupt (max size {max_size})"))]
+    CorruptHeader { max_size: u32 },
+
+    #[snafu(display("the frame is {actual} bytes long, expected {expected}"))]
+    FrameLength { expected: usize, actual: usize },
+
+    #[snafu(display(
+        "the frame ({width}x{height}, {frame_len} bytes) exceeds the maximum image size \
+         ({MAX_IMAGE_SIZE} bytes)"
+    ))]
+    FrameTooLarge {
+        width: u32,
+        height: u32,
+        frame_len: usize,
+    },
+
+    #[snafu(display("the raw frame is too short to hold a header"))]
+    RawTooShort,
+
+    #[snafu(display("the raw frame's header is invalid"))]
+    RawHeader { source: InvalidHeaderError },
+
+    #[snafu(display("the raw frame is {actual} bytes long, but its header implies {expected}"))]
+    RawLength { expected: usize, actual: usize },
+}
+
+/// Timings measured while publishing a single frame.
+#[derive(Debug, Default, Copy, Clone)]
+struct SendTimings {
+    /// Time spent holding the shared memory lock.
+    critical_section: Duration,
+    /// Time spent waiting for `WANT` before taking the lock.
+    want_wait: Duration,
+    /// Whether the wait for `WANT` timed out.
+    want_timed_out: bool,
+}
+
+impl SendFrameError {
+    fn code(&self) -> &'static str {
+        match self {
+            SendFrameError::WaitWant { .. } => "SEND_WAIT_WANT",
+            SendFrameError::ResetWant { .. } => "SEND_RESET_WANT",
+            SendFrameError::LockMutex {
+                source: LockMutexError::Timeout,
+            } => "SEND_LOCK_TIMEOUT",
+            SendFrameError::LockMutex { .. } => "SEND_LOCK_MUTEX",
+            SendFrameError::SignalSent { .. } => "SEND_SIGNAL_SENT",
+            SendFrameError::ResetSent { .. } => "SEND_RESET_SENT",
+            SendFrameError::WritePipe { .. } => "SEND_WRITE_PIPE",
+            SendFrameError::WaitCompletion { .. } => "SEND_WAIT_COMPLETION",
+            SendFrameError::NoImage => "SEND_NO_IMAGE",
+            SendFrameError::CorruptHeader { .. } => "SEND_CORRUPT_HEADER",
+            SendFrameError::FrameLength { .. } => "SEND_FRAME_LENGTH",
+            SendFrameError::FrameTooLarge { .. } => "SEND_FRAME_TOO_LARGE",
+            SendFrameError::RawTooShort => "SEND_RAW_TOO_SHORT",
+            SendFrameError::RawHeader { .. } => "SEND_RAW_HEADER",
+            SendFrameError::RawLength { .. } => "SEND_RAW_LENGTH",
+        }
+    }
+
+    /// Returns `true` if the error suggests the connection to the receiver is broken.
+    pub fn is_disconnect(&self) -> bool {
+        matches!(
+            self,
+            SendFrameError::LockMutex {
+                source: LockMutexError::Os { .. },
+            } | SendFrameError::SignalSent { .. }
+                | SendFrameError::ResetSent { .. }
+                | SendFrameError::CorruptHeader { .. }
+                | SendFrameError::WritePipe { .. }
+        )
+    }
+}
+
+/// What a receiver would read from a device's shared memory at one point in time.
+#[derive(Debug)]
+pub struct Snapshot {
+    /// The header, as is.
+    pub header: Header,
+    /// The frame config the header describes, or why it isn't one a sender could have written.
+    pub config: Result<FrameConfig, InvalidHeaderError>,
+    /// The frame the header describes, or nothing if the header is invalid.
+    pub image: Vec<u8>,
+}
+
+impl Snapshot {
+    /// Copies the header and frame out of `bytes`, the whole shared memory region.
+    pub(crate) fn read(bytes: &[u8]) -> Snapshot {
+        let header = Header::read(bytes).expect("the region starts with a header");
+        let config = header.config();
+
+        let len = config
+            .as_ref()
+            .ok()
+            .filter(|config| check_frame_size(config).is_ok())
+            .map_or(0, FrameConfig::frame_len);
+
+        Snapshot {
+            header,
+            config,
+            image: bytes[HEADER_SIZE..][..len].to_vec(),
+        }
+    }
+}
+
+#[derive(Debug, Snafu)]
+#[snafu(module)]
+pub enum SnapshotError {
+    #[snafu(display("failed to open the mutex"))]
+    OpenMutex { source: OpenMutexError },
+
+    #[snafu(display("failed to open the shared memory"))]
+    OpenSharedMemory { source: OpenFileMappingError },
+
+    #[snafu(display("failed to lock the mutex"))]
+    LockMutex { source: LockMutexError },
+}
+
+/// Reads what the receiver of `names` would see right now, waiting at most `lock_timeout` for
+/// the lock.
+///
+/// Nothing is written, and the shared memory is opened for reading only, so this works even
+/// where a sender couldn't connect for lack of write access.
+pub fn read_snapshot(
+    names: &DeviceNames,
+    lock_timeout: Duration,
+) -> Result<Snapshot, SnapshotError> {
+    let mutex = Mutex::open_existing(&names.mutex).context(snapshot_error::OpenMutexSnafu)?;
+
+    // SAFETY: the receiver creates the mapping with this size (see `Uninit::try_init`).
+    let mapping =
+        unsafe { FileMapping::open_existing_readonly(&names.data, HEADER_SIZE + MAX_IMAGE_SIZE) }
+            .context(snapshot_error::OpenSharedMemorySnafu)?;
+
+    // SAFETY: the mapping is only accessed while holding the mutex.
+    let mut shared = unsafe { SharedMemory::new(mapping, mutex) };
+
+    shared
+        .with_ref(lock_timeout, |bytes, _| Snapshot::read(bytes))
+        .context(snapshot_error::LockMutexSnafu)
+}
+
+/// Checks that a frame of `config` fits in the image region. Every path that publishes a
+/// frame goes through this, since `FrameConfig` only limits the dimensions.
+pub fn check_frame_size(config: &FrameConfig) -> Result<(), SendFrameError> {
+    ensure!(
+        config.frame_len() <= MAX_IMAGE_SIZE,
+        send_frame_error::FrameTooLargeSnafu {
+            width: config.width,
+            height: config.height,
+            frame_len: config.frame_len(),
+        }
+    );
+
+    Ok(())
+}
+
+/// A destination frames can be published to.
+trait FrameSink {
+    /// Fills the header for `config`, lets `f` write the image (exactly
+    /// [`FrameConfig::frame_len`] bytes) and publishes the frame.
+    fn try_send_with<F>(
+        &mut self,
+        config: FrameConfig,
+        f: F,
+    ) -> Result<SendTimings, SendFrameError>
+    where
+        F: FnOnce(&mut [u8]);
+
+    /// Rewrites the header for `config` and republishes the previously sent image.
+    fn try_apply_config(&mut self, config: FrameConfig) -> Result<(), SendFrameError>;
+
+    /// Republishes the previously sent frame as is.
+    fn try_keep_alive(&mut self) -> Result<(), SendFrameError>;
+
+    /// Publishes `blob`, an already validated `header + image`, as is.
+    fn try_send_raw(&mut self, blob: &[u8]) -> Result<SendTimings, SendFrameError>;
+}
+
+/// Checks that the header's `max_size`, written by the receiver, is still intact.
+fn check_header(header: &Header) -> Result<(), SendFrameError> {
+    let max_size = header.max_size;
+
+    ensure!(
+        max_size as usize == MAX_IMAGE_SIZE,
+        send_frame_error::CorruptHeaderSnafu { max_size }
+    );
+
+    Ok(())
+}
+
+/// Splits the shared memory into the `Header` at its start and the image region after it.
+///
+/// # Panics
+/// - `bytes` must be at least as long as a `Header` and suitably aligned for one.
+fn split_frame(bytes: &mut [u8]) -> (&mut Header, &mut [u8]) {
+    let (header_bytes, image_bytes) = bytes.split_at_mut(size_of::<Header>());
+    let header_ptr: *mut Header = header_bytes.as_mut_ptr().cast();
+    assert!(header_ptr.is_aligned());
+
+    // SAFETY:
+    // - `header` isn't null, since `header_bytes` is not empty.
+    // - We have exclusive access to `header_bytes`.
+    // - `header_bytes.len()` equals `size_of::<Header>()`.
+    // - `header_ptr` is properly aligned for `Header`.
+    // - `Header` can hold arbitrary bit patterns.
+    let header = unsafe { header_ptr.as_mut().unwrap_unchecked() };
+
+    (header, image_bytes)
 }
 
 #[derive(Debug, Default)]
@@ -122,29 +878,35 @@ struct Uninit {
 }
 
 impl Uninit {
-    fn try_init(&mut self) -> Result<Ready, InitError> {
+    fn try_init(&mut self, options: &SenderOptions) -> Result<Ready, InitError> {
+        let names = &options.device;
+
         // `[u8]` has 1 byte alignment, so there is no padding.
-        const SHARED_DATA_SIZE: usize = size_of::<Header>() + MAX_IMAGE_SIZE;
+        const SHARED_DATA_SIZE: usize = HEADER_SIZE + MAX_IMAGE_SIZE;
 
         let mutex = self
             .mutex
-            .try_get_or_insert_with(|| Mutex::open_existing("UnityCapture_Mutx"))
+            .try_get_or_insert_with(|| Mutex::open_existing(&names.mutex))
             .context(init_error::OpenMutexSnafu)?;
 
         let mapping = mutex
-            .with_lock(|| {
+            .with_lock_timeout(options.lock_timeout, |_| {
+                // `WANT` is an auto-reset event, so every signal releases exactly one wait.
+                // (The sender only waits on it if `SenderOptions::want_timeout` is set, or to
+                // confirm a frame.) It may exist already, e.g. created by a frame request
+                // watcher, in which case both share it.
                 self.want_frame.try_get_or_insert_with(|| {
-                    Event::create_new("UnityCapture_Want").context(init_error::CreateWantEventSnafu)
+                    Event::create_new(&names.want)
+                        .map(|(event, _)| event)
+                        .context(init_error::CreateWantEventSnafu)
                 })?;
 
                 self.sent_frame.try_get_or_insert_with(|| {
-                    Event::open_existing("UnityCapture_Sent")
-                        .context(init_error::OpenSentEventSnafu)
+                    Event::open_existing(&names.sent).context(init_error::OpenSentEventSnafu)
                 })?;
 
-                let mapping =
-                    unsafe { FileMapping::open_existing("UnityCapture_Data", SHARED_DATA_SIZE) }
-                        .context(init_error::OpenSharedMemorySnafu)?;
+                let mapping = unsafe { FileMapping::open_existing(&names.data, SHARED_DATA_SIZE) }
+                    .context(init_error::OpenSharedMemorySnafu)?;
 
                 Ok(mapping)
             })
@@ -155,91 +917,2271 @@ impl Uninit {
         let want_frame = self.want_frame.take().unwrap();
         let sent_frame = self.sent_frame.take().unwrap();
 
-        let shared = unsafe { SharedMemory::new(mapping, mutex) };
+        let mut shared = unsafe { SharedMemory::new(mapping, mutex) };
+
+        // The receiver writes `max_size` when it creates the mapping. A different value means
+        // the image region isn't laid out the way we expect.
+        let max_size = shared
+            .with(options.lock_timeout, |bytes, _abandoned| {
+                split_frame(bytes).0.max_size
+            })
+            .context(init_error::LockMutexSnafu)?;
+
+        ensure!(
+            max_size as usize == MAX_IMAGE_SIZE,
+            init_error::IncompatibleMappingSnafu {
+                found: max_size,
+                expected: MAX_IMAGE_SIZE,
+            }
+        );
 
         Ok(Ready {
-            _want_frame: want_frame,
+            want_frame,
             sent_frame,
             shared,
+            lock_timeout: options.lock_timeout,
+            sent_signal: options.sent_signal,
+            want_timeout: options.want_timeout,
+            last_config: None,
+            has_image: false,
         })
     }
 }
 
+impl Uninit {
+    /// Drops the objects opened by previous attempts.
+    fn reset(&mut self) {
+        *self = Uninit::default();
+    }
+
+    /// Connects via the shared memory, falling back to the pipe if one is configured and the
+    /// error is terminal. Transient errors, e.g. the receiver not having created its objects
+    /// yet, are returned as is, so that a sender that is just starting up isn't stuck with the
+    /// pipe.
+    ///
+    /// Failed attempts are retried up to [`SenderOptions::open_retries`] times. A terminal
+    /// error (see [`InitError::is_terminal`]) isn't retried and resets `self`, so that the next
+    /// attempt reopens everything.
+    fn try_connect(&mut self, options: &SenderOptions) -> Result<Transport, InitError> {
+        let mut attempts = 1;
+        let mut result = self.try_connect_with_fallback(options);
+
+        while let Err(e) = &result
+            && !e.is_terminal()
+            && attempts <= options.open_retries
+        {
+            thread::sleep(options.open_retry_interval);
+            attempts += 1;
+            result = self.try_connect_with_fallback(options);
+        }
+
+        if attempts > 1 {
+            result = result.context(init_error::RetriedSnafu { attempts });
+        }
+
+        if let Err(e) = &result
+            && e.is_terminal()
+        {
+            self.reset();
+        }
+
+        result
+    }
+
+    fn try_connect_with_fallback(
+        &mut self,
+        options: &SenderOptions,
+    ) -> Result<Transport, InitError> {
+        match self.try_init(options) {
+            Ok(ready) => Ok(Transport::SharedMemory(ready)),
+            Err(e) => {
+                match &options.pipe_fallback {
+                    Some(name) if e.is_terminal() => PipeSink::open(name)
+                        .map(Transport::Pipe)
+                        .context(init_error::OpenPipeSnafu {
+                            shared_memory: Box::new(e),
+                        }),
+                    _ => Err(e),
+                }
+            }
+        }
+    }
+}
+
 #[derive(Debug)]
 struct Ready {
-    _want_frame: Event,
+    want_frame: Event,
     sent_frame: Event,
     shared: SharedMemory,
+    lock_timeout: Duration,
+    sent_signal: SentSignal,
+    want_timeout: Option<Duration>,
+    // The config the header currently reflects. `None` right after connecting, so that
+    // the first frame always writes the header.
+    last_config: Option<FrameConfig>,
+    // Whether the image region holds a frame we have sent.
+    has_image: bool,
 }
 
 impl Ready {
-    fn try_send_with<F>(&mut self, config: FrameConfig, f: F) -> Result<(), SendFrameError>
-    where
-        F: FnOnce(&mut [u8]),
-    {
-        self.shared
-            .with(|bytes| {
-                let (header_bytes, image_bytes) = bytes.split_at_mut(size_of::<Header>());
+    /// Closes the events and the shared memory, reporting the first failure to close one.
+    fn close(self) -> Result<(), CloseError> {
+        let closed_want = self.want_frame.close();
+        let closed_sent = self.sent_frame.close();
+        let closed_shared = self.shared.close();
+        closed_want.and(closed_sent).and(closed_shared)
+    }
 
-                let header_ptr: *mut Header = header_bytes.as_mut_ptr().cast();
-                assert!(header_bytes.len() == size_of::<Header>() && header_ptr.is_aligned());
+    /// Waits for `WANT` if a timeout is set, returning how long that took and whether it timed
+    /// out. Timing out isn't an error: the frame is published anyway.
+    fn wait_for_want(&self) -> Result<(Duration, bool), SendFrameError> {
+        let Some(timeout) = self.want_timeout else {
+            return Ok((Duration::ZERO, false));
+        };
 
-                // SAFETY:
-                // - `header` isn't null, since `header_bytes` is not empty.
-                // - We have exclusive access to `header_bytes`.
-                // - `header_bytes.len()` equals `size_of::<Header>()`.
-                // - `header_ptr` is properly aligned for `Header`.
-                // - `Header` can hold arbitrary bit patterns.
-                let header = unsafe { header_ptr.as_mut().unwrap_unchecked() };
-                header.fill(config.width as c_int, config.height as c_int);
+        let start = Instant::now();
 
-                f(image_bytes);
-            })
-            .context(send_frame_error::LockMutexSnafu)?;
+        match self.want_frame.wait_timeout(timeout) {
+            Ok(()) => Ok((start.elapsed(), false)),
+            Err(WaitEventError::Timeout) => Ok((start.elapsed(), true)),
+            Err(e) => Err(e).context(send_frame_error::WaitWantSnafu),
+        }
+    }
+
+    fn signal_sent(&self) -> Result<(), SendFrameError> {
+        match self.sent_signal {
+            SentSignal::Set | SentSignal::SetThenReset => self.sent_frame.set(),
+            SentSignal::Pulse => self.sent_frame.pulse(),
+        }
+        .context(send_frame_error::SignalSentSnafu)?;
 
-        self.sent_frame
-            .set()
-            .context(send_frame_error::SignalSentSnafu)?;
+        if self.sent_signal == SentSignal::SetThenReset {
+            self.sent_frame
+                .reset()
+                .context(send_frame_error::ResetSentSnafu)?;
+        }
 
         Ok(())
     }
 }
 
-enum State {
-    Uninit(Uninit),
-    Ready(Ready),
-}
+impl FrameSink for Ready {
+    fn try_send_with<F>(&mut self, config: FrameConfig, f: F) -> Result<SendTimings, SendFrameError>
+    where
+        F: FnOnce(&mut [u8]),
+    {
+        let (want_wait, want_timed_out) = self.wait_for_want()?;
+        let fill_header = self.last_config != Some(config);
 
-pub struct Sender {
-    state: State,
-}
+        let critical_section = self
+            .shared
+            .with(self.lock_timeout, |bytes, abandoned| {
+                let start = Instant::now();
+                let (header, image_bytes) = split_frame(bytes);
 
-impl Sender {
-    pub fn new() -> Sender {
-        Sender {
-            state: State::Uninit(Uninit::default()),
-        }
-    }
+                if abandoned {
+                    check_header(header)?;
+                }
 
-    pub fn try_send_with(
-        &mut self,
-        config: FrameConfig,
+                // The previous owner may have left a half-written header behind.
+                if fill_header || abandoned {
+                    header.fill(config)?;
+                }
+
+                // Bounded, so that writing past the frame panics rather than going unnoticed.
+                f(&mut image_bytes[..config.frame_len()]);
+
+                Ok(start.elapsed())
+            })
+            .context(send_frame_error::LockMutexSnafu)??;
+
+        self.last_config = Some(config);
+        self.has_image = true;
+
+        self.signal_sent()?;
+
+        Ok(SendTimings {
+            critical_section,
+            want_wait,
+            want_timed_out,
+        })
+    }
+
+    fn try_apply_config(&mut self, config: FrameConfig) -> Result<(), SendFrameError> {
+        ensure!(self.has_image, send_frame_error::NoImageSnafu);
+
+        self.shared
+            .with(self.lock_timeout, |bytes, abandoned| {
+                let (header, _) = split_frame(bytes);
+
+                if abandoned {
+                    check_header(header)?;
+                }
+
+                header.fill(config)
+            })
+            .context(send_frame_error::LockMutexSnafu)??;
+
+        self.last_config = Some(config);
+
+        self.signal_sent()
+    }
+
+    fn try_keep_alive(&mut self) -> Result<(), SendFrameError> {
+        ensure!(self.has_image, send_frame_error::NoImageSnafu);
+
+        // The image region still holds the frame, so only the consumer needs telling.
+        self.signal_sent()
+    }
+
+    fn try_send_raw(&mut self, blob: &[u8]) -> Result<SendTimings, SendFrameError> {
+        let (want_wait, want_timed_out) = self.wait_for_want()?;
+
+        let critical_section = self
+            .shared
+            // The blob replaces the header too, so there is nothing to check if abandoned.
+            .with(self.lock_timeout, |bytes, _abandoned| {
+                let start = Instant::now();
+                bytes[..blob.len()].copy_from_slice(blob);
+                start.elapsed()
+            })
+            .context(send_frame_error::LockMutexSnafu)?;
+
+        // The header came from elsewhere, so the next regular frame rewrites it.
+        self.last_config = None;
+        self.has_image = true;
+
+        self.signal_sent()?;
+
+        Ok(SendTimings {
+            critical_section,
+            want_wait,
+            want_timed_out,
+        })
+    }
+}
+
+/// Publishes frames by writing `header + image` to a named pipe.
+///
+/// Used when the shared memory objects can't be opened, e.g. across session boundaries.
+#[derive(Debug)]
+struct PipeSink {
+    pipe: Pipe,
+    buffer: Vec<u8>,
+}
+
+impl PipeSink {
+    fn open(name: &str) -> Result<Self, OpenPipeError> {
+        Ok(PipeSink {
+            pipe: Pipe::open_existing(name)?,
+            buffer: Vec::new(),
+        })
+    }
+
+    fn close(self) -> Result<(), CloseError> {
+        self.pipe.close()
+    }
+}
+
+impl FrameSink for PipeSink {
+    fn try_send_with<F>(&mut self, config: FrameConfig, f: F) -> Result<SendTimings, SendFrameError>
+    where
+        F: FnOnce(&mut [u8]),
+    {
+        let mut header = Header::new();
+        header.fill(config)?;
+
+        self.buffer.clear();
+        self.buffer.extend_from_slice(header.as_bytes());
+        self.buffer.resize(HEADER_SIZE + config.frame_len(), 0);
+
+        f(&mut self.buffer[HEADER_SIZE..]);
+
+        self.pipe
+            .write_all(&self.buffer)
+            .context(send_frame_error::WritePipeSnafu)?;
+
+        // Nothing is shared with the consumer, so there is no critical section.
+        Ok(SendTimings::default())
+    }
+
+    fn try_apply_config(&mut self, config: FrameConfig) -> Result<(), SendFrameError> {
+        ensure!(!self.buffer.is_empty(), send_frame_error::NoImageSnafu);
+
+        let mut header = Header::new();
+        header.fill(config)?;
+
+        self.buffer[..HEADER_SIZE].copy_from_slice(header.as_bytes());
+        self.buffer.resize(HEADER_SIZE + config.frame_len(), 0);
+
+        self.pipe
+            .write_all(&self.buffer)
+            .context(send_frame_error::WritePipeSnafu)
+    }
+
+    fn try_keep_alive(&mut self) -> Result<(), SendFrameError> {
+        ensure!(!self.buffer.is_empty(), send_frame_error::NoImageSnafu);
+
+        self.pipe
+            .write_all(&self.buffer)
+            .context(send_frame_error::WritePipeSnafu)
+    }
+
+    fn try_send_raw(&mut self, blob: &[u8]) -> Result<SendTimings, SendFrameError> {
+        self.buffer.clear();
+        self.buffer.extend_from_slice(blob);
+
+        self.pipe
+            .write_all(&self.buffer)
+            .context(send_frame_error::WritePipeSnafu)?;
+
+        Ok(SendTimings::default())
+    }
+}
+
+/// The transport picked at connect.
+#[derive(Debug)]
+enum Transport {
+    SharedMemory(Ready),
+    Pipe(PipeSink),
+}
+
+impl Transport {
+    fn close(self) -> Result<(), CloseError> {
+        match self {
+            Transport::SharedMemory(ready) => ready.close(),
+            Transport::Pipe(pipe) => pipe.close(),
+        }
+    }
+}
+
+impl FrameSink for Transport {
+    fn try_send_with<F>(&mut self, config: FrameConfig, f: F) -> Result<SendTimings, SendFrameError>
+    where
+        F: FnOnce(&mut [u8]),
+    {
+        match self {
+            Transport::SharedMemory(ready) => ready.try_send_with(config, f),
+            Transport::Pipe(pipe) => pipe.try_send_with(config, f),
+        }
+    }
+
+    fn try_apply_config(&mut self, config: FrameConfig) -> Result<(), SendFrameError> {
+        match self {
+            Transport::SharedMemory(ready) => ready.try_apply_config(config),
+            Transport::Pipe(pipe) => pipe.try_apply_config(config),
+        }
+    }
+
+    fn try_keep_alive(&mut self) -> Result<(), SendFrameError> {
+        match self {
+            Transport::SharedMemory(ready) => ready.try_keep_alive(),
+            Transport::Pipe(pipe) => pipe.try_keep_alive(),
+        }
+    }
+
+    fn try_send_raw(&mut self, blob: &[u8]) -> Result<SendTimings, SendFrameError> {
+        match self {
+            Transport::SharedMemory(ready) => ready.try_send_raw(blob),
+            Transport::Pipe(pipe) => pipe.try_send_raw(blob),
+        }
+    }
+}
+
+/// The connection state of a [`Sender`].
+///
+/// A sender starts out `Disconnected`. The first frame moves it to `Connecting` while the
+/// receiver's objects are opened, then to `Connected`, or back to `Disconnected` if that
+/// fails (retried on the next frame). A send failure that suggests the receiver went away
+/// (see [`SendFrameError::is_disconnect`]) drops the connection, so the next frame reconnects
+/// and re-maps the shared memory.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ConnectionState {
+    Disconnected,
+    Connecting,
+    Connected,
+}
+
+/// Notified about every [`ConnectionState`] transition.
+pub type StateListener = Box<dyn Fn(ConnectionState) + Send>;
+
+enum State {
+    // Keeps the objects opened by a failed attempt for the next one.
+    Disconnected(Uninit),
+    Connected(Transport),
+}
+
+/// How the `SENT` event is signaled after a frame is published.
+///
+/// The driver waits on `SENT` between frames, so `Set` always wakes it, even if it starts
+/// waiting late. `Pulse` and `SetThenReset` only wake a consumer that is waiting at that very
+/// moment, and drop frames it is busy with; they exist for consumers that treat a signaled
+/// `SENT` as level-triggered.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum SentSignal {
+    /// `SetEvent`.
+    #[default]
+    Set,
+    /// `PulseEvent`.
+    Pulse,
+    /// `SetEvent` immediately followed by `ResetEvent`.
+    SetThenReset,
+}
+
+/// Settings that persist across (re)connects.
+#[derive(Debug, Clone)]
+pub struct SenderOptions {
+    /// How long to wait for the data mutex before giving up on a frame.
+    /// `Duration::MAX` waits indefinitely.
+    pub lock_timeout: Duration,
+    /// The named pipe to publish to if the shared memory can't be opened.
+    pub pipe_fallback: Option<String>,
+    /// The longest the shared memory lock should be held per frame. Frames exceeding it
+    /// are counted in [`SendStats::over_budget`].
+    pub critical_section_budget: Option<Duration>,
+    /// The names of the receiver's shared objects.
+    pub device: DeviceNames,
+    /// How `SENT` is signaled.
+    pub sent_signal: SentSignal,
+    /// How long to wait for the consumer to ask for a frame (signal `WANT`) before publishing
+    /// it anyway. `None` publishes right away, leaving the pacing to the caller.
+    pub want_timeout: Option<Duration>,
+    /// How many more times a failed connect is attempted before giving up on a frame, e.g.
+    /// while the driver is still starting.
+    pub open_retries: u32,
+    /// How long to sleep between connect attempts.
+    pub open_retry_interval: Duration,
+    /// Whether [`Sender::try_send`] accepts frames shorter than [`FrameConfig::frame_len`],
+    /// zeroing the rest of the image instead of failing.
+    pub pad_short_frames: bool,
+}
+
+impl Default for SenderOptions {
+    fn default() -> Self {
+        Self {
+            lock_timeout: Duration::MAX,
+            pipe_fallback: None,
+            critical_section_budget: None,
+            device: DeviceNames::default(),
+            sent_signal: SentSignal::Set,
+            want_timeout: None,
+            open_retries: 0,
+            open_retry_interval: Duration::from_millis(50),
+            pad_short_frames: false,
+        }
+    }
+}
+
+/// Counters describing what happened to the frames handed to a `Sender`.
+///
+/// Publishing a frame consists of waiting for `WANT` (if [`SenderOptions::want_timeout`] is
+/// set) and the critical section, which are timed separately.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct SendStats {
+    /// Frames published.
+    pub published: u64,
+    /// Frames skipped because the frame validator returned `false`.
+    pub rejected: u64,
+    /// Frames skipped because they came sooner than [`FrameConfig::min_frame_interval`] allows.
+    pub throttled: u64,
+    /// The longest time the shared memory lock was held for a single frame.
+    pub max_critical_section: Duration,
+    /// The total time the shared memory lock was held, i.e. spent copying frames.
+    pub total_critical_section: Duration,
+    /// The total time spent waiting for the consumer to signal `WANT`.
+    pub total_want_wait: Duration,
+    /// Frames that held the lock longer than [`SenderOptions::critical_section_budget`].
+    pub over_budget: u64,
+    /// Frames published after waiting for `WANT` timed out.
+    pub want_timeouts: u64,
+    /// Frames given up on because the shared memory lock couldn't be taken in time.
+    pub lock_timeouts: u64,
+    /// Failed attempts to connect to the receiver.
+    pub init_failures: u64,
+}
+
+/// The outcome of [`Sender::try_send_and_confirm`].
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Confirmation {
+    /// Whether the frame was published (rather than rejected by the frame validator).
+    pub published: bool,
+    /// Whether the consumer asked for the next frame in time.
+    pub confirmed: bool,
+    /// How long was spent waiting for the consumer.
+    pub waited: Duration,
+}
+
+/// What [`Sender::try_send_if_wanted`] did with a frame.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SendOutcome {
+    /// The frame was published.
+    Sent,
+    /// The consumer wasn't asking for a frame.
+    Skipped,
+    /// The frame validator returned `false`.
+    Rejected,
+    /// The previous frame was published less than [`FrameConfig::min_frame_interval`] ago.
+    Throttled,
+}
+
+impl SendOutcome {
+    /// Maps the result of [`Sender::try_send_with`] once the frame has been checked, when only
+    /// throttling can keep it from being published.
+    fn from_published(published: bool) -> SendOutcome {
+        if published {
+            SendOutcome::Sent
+        } else {
+            SendOutcome::Throttled
+        }
+    }
+}
+
+/// Decides whether a frame should be published.
+pub type FrameValidator = Box<dyn Fn(&[u8]) -> bool + Send>;
+
+pub struct Sender {
+    state: State,
+    options: SenderOptions,
+    frame_validator: Option<FrameValidator>,
+    state_listener: Option<StateListener>,
+    stats: SendStats,
+    last_published: Option<Instant>,
+    last_win32_error: Option<u32>,
+}
+
+impl Sender {
+    pub fn new(options: SenderOptions) -> Sender {
+        Sender {
+            state: State::Disconnected(Uninit::default()),
+            options,
+            frame_validator: None,
+            state_listener: None,
+            stats: SendStats::default(),
+            last_published: None,
+            last_win32_error: None,
+        }
+    }
+
+    pub fn connection_state(&self) -> ConnectionState {
+        match self.state {
+            State::Disconnected(_) => ConnectionState::Disconnected,
+            State::Connected(_) => ConnectionState::Connected,
+        }
+    }
+
+    /// Sets a listener that is notified about every connection state transition.
+    pub fn set_state_listener(&mut self, listener: Option<StateListener>) {
+        self.state_listener = listener;
+    }
+
+    /// Sets a validator that is run over every frame passed to [`Sender::try_send`]
+    /// before it is copied. Frames it returns `false` for are skipped and counted
+    /// in [`SendStats::rejected`].
+    ///
+    /// The validator runs once per frame, so it should be cheap.
+    pub fn set_frame_validator(&mut self, validator: Option<FrameValidator>) {
+        self.frame_validator = validator;
+    }
+
+    pub fn stats(&self) -> SendStats {
+        self.stats
+    }
+
+    /// Returns the Win32 error code behind the most recent failure to connect, send or close
+    /// the connection's handles, or `None` if there was none or it didn't come from the OS.
+    pub fn last_win32_error(&self) -> Option<u32> {
+        self.last_win32_error
+    }
+
+    /// Resets the counters, keeping everything else.
+    pub fn reset_stats(&mut self) {
+        self.stats = SendStats::default();
+    }
+
+    /// Resets the counters and drops cached state (the last written header config, the last
+    /// sent frame, the frame rate limiter and the last Win32 error), keeping the connection
+    /// open.
+    ///
+    /// The next frame rewrites the header, and until one is sent,
+    /// [`Sender::try_keep_alive`] and [`Sender::try_apply_config`] fail with
+    /// [`SendFrameError::NoImage`].
+    pub fn reset_state(&mut self) {
+        self.stats = SendStats::default();
+        self.last_published = None;
+        self.last_win32_error = None;
+
+        match &mut self.state {
+            State::Connected(Transport::SharedMemory(ready)) => {
+                ready.last_config = None;
+                ready.has_image = false;
+            }
+            State::Connected(Transport::Pipe(pipe)) => pipe.buffer = Vec::new(),
+            State::Disconnected(_) => {}
+        }
+    }
+
+    /// Replaces the options, applying them to the current connection where possible.
+    ///
+    /// Selecting another device drops the connection (see [`Sender::reset`]), so that the next
+    /// frame connects to the new one. A changed pipe fallback only takes effect on the next
+    /// connect.
+    pub fn set_options(&mut self, options: SenderOptions) {
+        if options.device != self.options.device {
+            // The connection, or the objects kept from a failed attempt, belong to the old
+            // device.
+            self.reset();
+        }
+
+        if let State::Connected(Transport::SharedMemory(ready)) = &mut self.state {
+            ready.lock_timeout = options.lock_timeout;
+            ready.sent_signal = options.sent_signal;
+            ready.want_timeout = options.want_timeout;
+        }
+
+        self.options = options;
+    }
+
+    /// Validates `frame` and publishes it, copying it to the start of the image region.
+    ///
+    /// `frame` must be exactly [`FrameConfig::frame_len`] bytes long, or no longer if
+    /// [`SenderOptions::pad_short_frames`] is set, in which case the rest of the image is
+    /// zeroed. Returns `false` if the frame validator rejected the frame.
+    pub fn try_send(&mut self, config: FrameConfig, frame: &[u8]) -> Result<bool, Error> {
+        if !self.check_frame(config, frame)? {
+            return Ok(false);
+        }
+
+        self.copy_frame(config, frame)
+    }
+
+    /// Checks `frame` the way [`Sender::try_send`] does, without publishing it. Returns `false`
+    /// if the frame validator rejected the frame.
+    fn check_frame(&mut self, config: FrameConfig, frame: &[u8]) -> Result<bool, Error> {
+        check_frame_size(&config).context(SendSnafu)?;
+
+        let too_short_is_ok = self.options.pad_short_frames && frame.len() < config.frame_len();
+
+        if frame.len() != config.frame_len() && !too_short_is_ok {
+            return send_frame_error::FrameLengthSnafu {
+                expected: config.frame_len(),
+                actual: frame.len(),
+            }
+            .fail()
+            .context(SendSnafu);
+        }
+
+        if let Some(validator) = &self.frame_validator
+            && !validator(frame)
+        {
+            self.stats.rejected += 1;
+            return Ok(false);
+        }
+
+        Ok(true)
+    }
+
+    /// Publishes a frame [`Sender::check_frame`] accepted, zeroing the rest of the image if it
+    /// is short.
+    fn copy_frame(&mut self, config: FrameConfig, frame: &[u8]) -> Result<bool, Error> {
+        self.try_send_with(config, |data| {
+            data[0..frame.len()].copy_from_slice(frame);
+            data[frame.len()..config.frame_len()].fill(0);
+        })
+    }
+
+    /// Publishes `frames` one after another, each under its own lock, stopping at the first
+    /// error.
+    ///
+    /// Unless [`SenderOptions::want_timeout`] is set, the frames are sent back to back without
+    /// waiting in between. Returns the number of frames published (rejected
+    /// frames don't count) along with the error that stopped the batch, if any.
+    pub fn try_send_batch(
+        &mut self,
+        config: FrameConfig,
+        frames: &[&[u8]],
+    ) -> (usize, Result<(), Error>) {
+        let mut sent = 0;
+
+        for frame in frames {
+            match self.try_send(config, frame) {
+                Ok(published) => sent += usize::from(published),
+                Err(e) => return (sent, Err(e)),
+            }
+        }
+
+        (sent, Ok(()))
+    }
+
+    /// Like [`Sender::try_send`], but afterwards waits up to `timeout` for the consumer to signal
+    /// `completion`.
+    ///
+    /// Returns `true` only if the frame was published and `completion` was signaled in time.
+    pub fn try_send_and_wait(
+        &mut self,
+        config: FrameConfig,
+        frame: &[u8],
+        completion: &Event,
+        timeout: Duration,
+    ) -> Result<bool, Error> {
+        if !self.try_send(config, frame)? {
+            return Ok(false);
+        }
+
+        match completion.wait_timeout(timeout) {
+            Ok(()) => Ok(true),
+            Err(WaitEventError::Timeout) => Ok(false),
+            Err(e) => Err(e)
+                .context(send_frame_error::WaitCompletionSnafu)
+                .context(SendSnafu),
+        }
+    }
+
+    /// Like [`Sender::try_send`], but afterwards waits up to `timeout` for the consumer to ask
+    /// for the next frame (signal `WANT`), as a sign that it has taken this one.
+    ///
+    /// Requests made before the frame was sent don't count. Frames sent over the pipe are never
+    /// confirmed.
+    pub fn try_send_and_confirm(
+        &mut self,
+        config: FrameConfig,
+        frame: &[u8],
+        timeout: Duration,
+    ) -> Result<Confirmation, Error> {
+        if let Some(want_frame) = self.want_frame() {
+            want_frame
+                .reset()
+                .context(send_frame_error::ResetWantSnafu)
+                .context(SendSnafu)?;
+        }
+
+        if !self.try_send(config, frame)? {
+            return Ok(Confirmation::default());
+        }
+
+        let Some(want_frame) = self.want_frame() else {
+            return Ok(Confirmation {
+                published: true,
+                ..Confirmation::default()
+            });
+        };
+
+        let start = Instant::now();
+
+        let confirmed = match want_frame.wait_timeout(timeout) {
+            Ok(()) => true,
+            Err(WaitEventError::Timeout) => false,
+            Err(e) => {
+                return Err(e)
+                    .context(send_frame_error::WaitWantSnafu)
+                    .context(SendSnafu);
+            }
+        };
+
+        Ok(Confirmation {
+            published: true,
+            confirmed,
+            waited: start.elapsed(),
+        })
+    }
+
+    /// Like [`Sender::try_send`], but only if the consumer is asking for a frame (has signaled
+    /// `WANT`) right now, returning [`SendOutcome::Skipped`] without waiting otherwise.
+    ///
+    /// Throttling, the frame's length and the frame validator are checked first, so that a
+    /// frame that wouldn't be published leaves the request pending. Frames sent over the pipe
+    /// are never skipped, since there is no `WANT` to check.
+    pub fn try_send_if_wanted(
+        &mut self,
+        config: FrameConfig,
+        frame: &[u8],
+    ) -> Result<SendOutcome, Error> {
+        if self.throttle(config) {
+            return Ok(SendOutcome::Throttled);
+        }
+
+        if !self.check_frame(config, frame)? {
+            return Ok(SendOutcome::Rejected);
+        }
+
+        self.ensure_ready().context(InitSnafu)?;
+
+        let Some(want_frame) = self.want_frame() else {
+            return self
+                .copy_frame(config, frame)
+                .map(SendOutcome::from_published);
+        };
+
+        match want_frame.wait_timeout(Duration::ZERO) {
+            Ok(()) => {}
+            Err(WaitEventError::Timeout) => return Ok(SendOutcome::Skipped),
+            Err(e) => {
+                return Err(e)
+                    .context(send_frame_error::WaitWantSnafu)
+                    .context(SendSnafu);
+            }
+        }
+
+        // Checking consumed the request, so waiting for it again would stall.
+        let want_timeout = self.replace_want_timeout(None);
+        let result = self.copy_frame(config, frame);
+        self.replace_want_timeout(want_timeout);
+
+        result.map(SendOutcome::from_published)
+    }
+
+    /// Replaces the current connection's `WANT` timeout, returning the previous one. The
+    /// options are left alone.
+    fn replace_want_timeout(&mut self, want_timeout: Option<Duration>) -> Option<Duration> {
+        match &mut self.state {
+            State::Connected(Transport::SharedMemory(ready)) => {
+                std::mem::replace(&mut ready.want_timeout, want_timeout)
+            }
+            _ => None,
+        }
+    }
+
+    fn want_frame(&self) -> Option<&Event> {
+        match &self.state {
+            State::Connected(Transport::SharedMemory(ready)) => Some(&ready.want_frame),
+            _ => None,
+        }
+    }
+
+    /// Connects to the receiver without publishing anything, so that the next frame doesn't
+    /// have to. An existing connection is kept as is.
+    pub fn try_connect(&mut self) -> Result<(), Error> {
+        self.ensure_ready().context(InitSnafu)?;
+        Ok(())
+    }
+
+    /// Publishes a frame written into the image region by `f`, which is given exactly
+    /// [`FrameConfig::frame_len`] bytes.
+    ///
+    /// Returns `false` without calling `f` if the previous frame was published less than
+    /// [`FrameConfig::min_frame_interval`] ago.
+    pub fn try_send_with(
+        &mut self,
+        config: FrameConfig,
         f: impl FnOnce(&mut [u8]),
-    ) -> Result<(), Error> {
-        self.ensure_ready()
+    ) -> Result<bool, Error> {
+        check_frame_size(&config).context(SendSnafu)?;
+
+        if self.throttle(config) {
+            return Ok(false);
+        }
+
+        let timings = self
+            .ensure_ready()
             .context(InitSnafu)?
             .try_send_with(config, f)
-            .context(SendSnafu)
+            .map_err(|e| self.on_send_error(e))?;
+
+        self.record(timings);
+        Ok(true)
+    }
+
+    /// Publishes `blob`, a ready-made `header + image` (e.g. produced by another sender),
+    /// without filling the header.
+    ///
+    /// The embedded header must describe a valid frame, and `blob` must be exactly as long as
+    /// that header and frame. The frame validator isn't run.
+    pub fn try_send_raw(&mut self, blob: &[u8]) -> Result<(), Error> {
+        validate_raw(blob).context(SendSnafu)?;
+
+        let timings = self
+            .ensure_ready()
+            .context(InitSnafu)?
+            .try_send_raw(blob)
+            .map_err(|e| self.on_send_error(e))?;
+
+        self.record(timings);
+        Ok(())
+    }
+
+    /// Checks whether a frame sent now would come sooner than
+    /// [`FrameConfig::min_frame_interval`] allows, counting it as throttled if so.
+    fn throttle(&mut self, config: FrameConfig) -> bool {
+        let throttled = self
+            .last_published
+            .is_some_and(|last| last.elapsed() < config.min_frame_interval());
+
+        self.stats.throttled += u64::from(throttled);
+        throttled
+    }
+
+    fn record(&mut self, timings: SendTimings) {
+        let critical_section = timings.critical_section;
+        self.last_published = Some(Instant::now());
+        self.stats.published += 1;
+        self.stats.max_critical_section = self.stats.max_critical_section.max(critical_section);
+        self.stats.total_critical_section += critical_section;
+        self.stats.total_want_wait += timings.want_wait;
+        self.stats.want_timeouts += u64::from(timings.want_timed_out);
+
+        if self
+            .options
+            .critical_section_budget
+            .is_some_and(|budget| critical_section > budget)
+        {
+            self.stats.over_budget += 1;
+        }
+    }
+
+    /// Rewrites the header for `config` and republishes the last sent image, without
+    /// copying a new one.
+    pub fn try_apply_config(&mut self, config: FrameConfig) -> Result<(), Error> {
+        check_frame_size(&config).context(SendSnafu)?;
+
+        match &mut self.state {
+            State::Connected(transport) => transport.try_apply_config(config),
+            State::Disconnected(_) => send_frame_error::NoImageSnafu.fail(),
+        }
+        .map_err(|e| self.on_send_error(e))
+    }
+
+    /// Republishes the last sent frame without touching the image or the header, e.g. to
+    /// keep the receiver's frame timeout from expiring while the source is paused.
+    pub fn try_keep_alive(&mut self) -> Result<(), Error> {
+        match &mut self.state {
+            State::Connected(transport) => transport.try_keep_alive(),
+            State::Disconnected(_) => send_frame_error::NoImageSnafu.fail(),
+        }
+        .map_err(|e| self.on_send_error(e))
+    }
+
+    /// Drops the connection if `e` suggests it is broken, so the next frame reconnects.
+    fn on_send_error(&mut self, e: SendFrameError) -> Error {
+        self.last_win32_error = win32_error_code(&e);
+
+        if let SendFrameError::LockMutex {
+            source: LockMutexError::Timeout,
+        } = e
+        {
+            self.stats.lock_timeouts += 1;
+        }
+
+        if e.is_disconnect() {
+            self.disconnect();
+        }
+
+        Error::Send { source: e }
+    }
+
+    /// Drops the connection, along with any objects kept from a failed attempt, so that the
+    /// next frame reopens everything from scratch. Settings and counters are kept.
+    pub fn reset(&mut self) {
+        match self.state {
+            State::Connected(_) => self.disconnect(),
+            State::Disconnected(_) => self.state = State::Disconnected(Uninit::default()),
+        }
+    }
+
+    /// Closes the connection's handles explicitly, so that a failure to close one shows up in
+    /// [`Sender::last_win32_error`].
+    fn disconnect(&mut self) {
+        if let State::Connected(_) = self.state {
+            let State::Connected(transport) =
+                std::mem::replace(&mut self.state, State::Disconnected(Uninit::default()))
+            else {
+                unreachable!()
+            };
+
+            if let Err(e) = transport.close() {
+                self.last_win32_error = win32_error_code(&e);
+            }
+
+            self.notify(ConnectionState::Disconnected);
+        }
+    }
+
+    fn notify(&self, state: ConnectionState) {
+        if let Some(listener) = &self.state_listener {
+            listener(state);
+        }
     }
 
-    fn ensure_ready<'a>(&'a mut self) -> Result<&'a mut Ready, InitError> {
-        if let State::Uninit(uninit) = &mut self.state {
-            self.state = State::Ready(uninit.try_init()?);
+    fn ensure_ready(&mut self) -> Result<&mut Transport, InitError> {
+        if let State::Disconnected(_) = self.state {
+            self.notify(ConnectionState::Connecting);
+
+            let State::Disconnected(uninit) = &mut self.state else {
+                unreachable!()
+            };
+
+            match uninit.try_connect(&self.options) {
+                Ok(transport) => {
+                    self.state = State::Connected(transport);
+                    self.notify(ConnectionState::Connected);
+                }
+                Err(e) => {
+                    self.stats.init_failures += 1;
+                    self.last_win32_error = win32_error_code(&e);
+                    self.notify(ConnectionState::Disconnected);
+                    return Err(e);
+                }
+            }
         }
 
         match &mut self.state {
-            State::Ready(ready) => Ok(ready),
-            State::Uninit(_) => unreachable!(),
+            State::Connected(transport) => Ok(transport),
+            State::Disconnected(_) => unreachable!(),
         }
     }
 }
+
+/// Checks that `blob` is a `header + image` with a valid header and a matching length.
+fn validate_raw(blob: &[u8]) -> Result<(), SendFrameError> {
+    let Some(header) = Header::read(blob) else {
+        return send_frame_error::RawTooShortSnafu.fail();
+    };
+
+    let config = header.config().context(send_frame_error::RawHeaderSnafu)?;
+
+    check_frame_size(&config)?;
+
+    let expected = HEADER_SIZE + config.frame_len();
+    ensure!(
+        blob.len() == expected,
+        send_frame_error::RawLengthSnafu {
+            expected,
+            actual: blob.len(),
+        }
+    );
+
+    Ok(())
+}
+
+impl Drop for Sender {
+    fn drop(&mut self) {
+        self.disconnect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::receiver::Receiver;
+    use crate::win32::ReadWrite;
+    use windows::Win32::Foundation::{ERROR_ACCESS_DENIED, ERROR_FILE_NOT_FOUND, WIN32_ERROR};
+    use windows::core::HRESULT;
+
+    fn config() -> FrameConfig {
+        FrameConfig::new(1280, 720, PixelFormat::Uint8).unwrap()
+    }
+
+    #[test]
+    fn header_holds_the_frame_timeout() {
+        for timeout_ms in [0, 500, c_int::MAX as u32] {
+            let mut header = Header::new();
+            header
+                .fill(config().with_frame_timeout_ms(timeout_ms).unwrap())
+                .unwrap();
+            assert_eq!(header.timeout, c_int::try_from(timeout_ms).unwrap());
+        }
+    }
+
+    #[test]
+    fn header_holds_the_default_frame_timeout() {
+        let mut header = Header::new();
+        header.fill(config()).unwrap();
+        assert_eq!(header.timeout, DEFAULT_FRAME_TIMEOUT_MS as c_int);
+    }
+
+    #[test]
+    fn header_stride_is_in_pixels() {
+        let mut header = Header::new();
+        header
+            .fill(FrameConfig::new(640, 480, PixelFormat::Uint8).unwrap())
+            .unwrap();
+
+        // 640 RGBA pixels are 2560 bytes, but the receiver does that multiplication itself.
+        assert_eq!(header.stride, 640);
+        assert_eq!(header.width, 640);
+    }
+
+    #[test]
+    fn header_rejects_frames_larger_than_the_image_region() {
+        let mut header = Header::new();
+        let result = header.fill(FrameConfig::new(8000, 8000, PixelFormat::Uint8).unwrap());
+
+        assert!(matches!(result, Err(SendFrameError::FrameTooLarge { .. })));
+        assert_eq!(header.as_bytes(), Header::new().as_bytes());
+    }
+
+    #[test]
+    fn frame_timeout_must_fit_in_a_c_int() {
+        assert_eq!(config().with_frame_timeout_ms(c_int::MAX as u32 + 1), None);
+        assert_eq!(config().with_frame_timeout_ms(u32::MAX), None);
+    }
+
+    #[test]
+    fn devices_resolve_distinct_names() {
+        let first = DeviceNames::for_device(OBJECT_NAME_BASE, 0);
+        let second = DeviceNames::for_device(OBJECT_NAME_BASE, 1);
+
+        assert_eq!(first.mutex, "UnityCapture_Mutx");
+        assert_eq!(second.mutex, "UnityCapture_Mutx2");
+        assert_eq!(second.want, "UnityCapture_Want2");
+        assert_eq!(second.sent, "UnityCapture_Sent2");
+        assert_eq!(second.data, "UnityCapture_Data2");
+        assert_ne!(first, second);
+        assert_eq!(first, DeviceNames::default());
+    }
+
+    #[test]
+    fn device_indices_are_limited_to_the_installed_devices() {
+        assert_eq!(
+            DeviceNames::try_for_device(OBJECT_NAME_BASE, MAX_DEVICES - 1).unwrap(),
+            DeviceNames::for_device(OBJECT_NAME_BASE, MAX_DEVICES - 1)
+        );
+        assert!(matches!(
+            DeviceNames::try_for_device(OBJECT_NAME_BASE, MAX_DEVICES),
+            Err(DeviceIndexError { index: MAX_DEVICES })
+        ));
+        assert!(DeviceNames::try_for_device(OBJECT_NAME_BASE, u32::MAX).is_err());
+    }
+
+    #[test]
+    fn device_names_take_a_namespace_prefix() {
+        let names = DeviceNames::for_device(OBJECT_NAME_BASE, 1).with_prefix("Global\\");
+
+        assert_eq!(names.mutex, "Global\\UnityCapture_Mutx2");
+        assert_eq!(names.want, "Global\\UnityCapture_Want2");
+        assert_eq!(names.sent, "Global\\UnityCapture_Sent2");
+        assert_eq!(names.data, "Global\\UnityCapture_Data2");
+    }
+
+    fn open_mutex_error(code: WIN32_ERROR) -> Error {
+        let source = windows::core::Error::from_hresult(HRESULT::from_win32(code.0));
+
+        Error::Init {
+            source: InitError::OpenMutex {
+                source: OpenMutexError::from_os(source, "UnityCapture_Mutx"),
+            },
+        }
+    }
+
+    #[test]
+    fn missing_mutex_is_retried() {
+        let e = open_mutex_error(ERROR_FILE_NOT_FOUND);
+
+        assert!(e.should_retry());
+        assert_eq!(e.code(), "INIT_NO_RECEIVER");
+    }
+
+    #[test]
+    fn denied_mutex_is_not_retried() {
+        let e = open_mutex_error(ERROR_ACCESS_DENIED);
+
+        assert!(!e.should_retry());
+        assert_eq!(e.code(), "INIT_OPEN_MUTEX");
+    }
+
+    #[test]
+    fn retried_errors_keep_their_classification() {
+        let Error::Init { source } = open_mutex_error(ERROR_ACCESS_DENIED) else {
+            unreachable!()
+        };
+
+        let e = Error::Init {
+            source: InitError::Retried {
+                source: Box::new(source),
+                attempts: 3,
+            },
+        };
+
+        assert!(!e.should_retry());
+        assert_eq!(e.code(), "INIT_OPEN_MUTEX");
+    }
+
+    #[test]
+    fn min_frame_interval_follows_max_fps() {
+        assert_eq!(config().min_frame_interval(), Duration::ZERO);
+
+        let config = config().with_max_fps(NonZeroU32::new(50));
+        assert_eq!(config.min_frame_interval(), Duration::from_millis(20));
+    }
+
+    #[test]
+    fn frames_sent_too_soon_are_throttled() {
+        let config = config().with_max_fps(NonZeroU32::new(1));
+        let mut sender = Sender::new(SenderOptions::default());
+
+        // Stands in for a first frame published just now, so that no receiver is needed.
+        sender.record(SendTimings::default());
+
+        let published = sender
+            .try_send_with(config, |_| panic!("a throttled frame must not be written"))
+            .unwrap();
+
+        assert!(!published);
+        assert_eq!(sender.stats().published, 1);
+        assert_eq!(sender.stats().throttled, 1);
+    }
+
+    #[test]
+    fn frame_config_accepts_the_maximum_dimensions() {
+        assert!(FrameConfig::new(MAX_WIDTH, 1, PixelFormat::Uint8).is_ok());
+        assert!(FrameConfig::new(1, MAX_HEIGHT, PixelFormat::Uint8).is_ok());
+        assert!(FrameConfig::new(MAX_WIDTH, MAX_HEIGHT, PixelFormat::Fp16Linear).is_ok());
+    }
+
+    #[test]
+    fn frame_config_rejects_dimensions_over_the_maximum() {
+        assert!(matches!(
+            FrameConfig::new(MAX_WIDTH + 1, 720, PixelFormat::Uint8),
+            Err(InvalidDimensionsError::TooLarge { .. })
+        ));
+        assert!(matches!(
+            FrameConfig::new(1280, MAX_HEIGHT + 1, PixelFormat::Uint8),
+            Err(InvalidDimensionsError::TooLarge { .. })
+        ));
+        assert_eq!(config().resized(MAX_WIDTH + 1, 720), None);
+    }
+
+    #[test]
+    fn frame_config_rejects_zero_dimensions() {
+        assert!(matches!(
+            FrameConfig::new(0, 720, PixelFormat::Uint8),
+            Err(InvalidDimensionsError::Zero {
+                width: 0,
+                height: 720
+            })
+        ));
+        assert!(matches!(
+            FrameConfig::new(1280, 0, PixelFormat::Uint8),
+            Err(InvalidDimensionsError::Zero {
+                width: 1280,
+                height: 0
+            })
+        ));
+    }
+
+    #[test]
+    fn frame_len_is_not_capped_at_the_image_size() {
+        let config = FrameConfig::new(8000, 8000, PixelFormat::Uint8).unwrap();
+        assert_eq!(config.frame_len(), 8000 * 8000 * 4);
+        assert!(matches!(
+            check_frame_size(&config),
+            Err(SendFrameError::FrameTooLarge {
+                width: 8000,
+                height: 8000,
+                ..
+            })
+        ));
+
+        let largest = FrameConfig::new(3840, 2160, PixelFormat::Fp16Linear).unwrap();
+        assert_eq!(largest.frame_len(), MAX_IMAGE_SIZE);
+        assert!(check_frame_size(&largest).is_ok());
+    }
+
+    #[test]
+    fn frames_too_large_for_the_image_region_are_rejected() {
+        let config = FrameConfig::new(8000, 8000, PixelFormat::Uint8).unwrap();
+        // No receiver is needed: the size is checked before connecting.
+        let mut sender = Sender::new(SenderOptions::default());
+        let is_too_large = |result: Result<_, Error>| {
+            matches!(
+                result,
+                Err(Error::Send {
+                    source: SendFrameError::FrameTooLarge { .. }
+                })
+            )
+        };
+
+        assert!(is_too_large(sender.try_send(config, &[0; 16])));
+        assert!(is_too_large(sender.try_send_with(config, |_| {
+            panic!("an oversized frame must not be written")
+        })));
+        assert!(is_too_large(sender.try_apply_config(config).map(|()| true)));
+
+        let mut header = Header::new();
+        header.width = 8000;
+        header.height = 8000;
+        assert!(matches!(
+            validate_raw(header.as_bytes()),
+            Err(SendFrameError::FrameTooLarge { .. })
+        ));
+        assert_eq!(sender.stats().published, 0);
+    }
+
+    #[test]
+    fn header_config_round_trips() {
+        let config = config()
+            .with_format(PixelFormat::Fp16Gamma)
+            .with_resize_mode(ResizeMode::Disabled)
+            .with_mirror_mode(MirrorMode::Horizontal);
+        let mut header = Header::new();
+        header.fill(config).unwrap();
+
+        assert_eq!(header.config().unwrap(), config);
+    }
+
+    #[test]
+    fn header_config_rejects_every_invalid_field() {
+        let header = || {
+            let mut header = Header::new();
+            header.fill(config()).unwrap();
+            header
+        };
+
+        let mut bad = header();
+        bad.max_size -= 1;
+        assert!(matches!(
+            bad.config(),
+            Err(InvalidHeaderError::MaxSize { .. })
+        ));
+
+        for (width, height) in [(0, 720), (-1, 720), (1280, 0), (1280, -1)] {
+            let mut bad = header();
+            bad.width = width;
+            bad.height = height;
+            bad.stride = width;
+            assert!(matches!(
+                bad.config(),
+                Err(InvalidHeaderError::Dimensions { .. })
+            ));
+        }
+
+        let mut bad = header();
+        bad.stride = 1280 * 4;
+        assert!(matches!(
+            bad.config(),
+            Err(InvalidHeaderError::Stride {
+                stride: 5120,
+                width: 1280
+            })
+        ));
+
+        let mut bad = header();
+        bad.format = 3;
+        assert!(matches!(
+            bad.config(),
+            Err(InvalidHeaderError::Format { code: 3 })
+        ));
+
+        let mut bad = header();
+        bad.resize_mode = 2;
+        assert!(matches!(
+            bad.config(),
+            Err(InvalidHeaderError::ResizeMode { code: 2 })
+        ));
+
+        let mut bad = header();
+        bad.mirror_mode = -1;
+        assert!(matches!(
+            bad.config(),
+            Err(InvalidHeaderError::MirrorMode { code: -1 })
+        ));
+
+        let mut bad = header();
+        bad.timeout = -1;
+        assert!(matches!(
+            bad.config(),
+            Err(InvalidHeaderError::Timeout { timeout: -1 })
+        ));
+    }
+
+    /// Returns the names of a device no other test (or process) uses.
+    fn test_device(name: &str) -> DeviceNames {
+        test_device_at(name, 0)
+    }
+
+    /// Like [`test_device`], but for the device at `index`.
+    fn test_device_at(name: &str, index: u32) -> DeviceNames {
+        let base = format!("node-vcam-test-{}-{name}", std::process::id());
+        DeviceNames::for_device(&base, index)
+    }
+
+    #[test]
+    fn only_devices_with_a_receiver_are_available() {
+        let base = format!("node-vcam-test-{}-list", std::process::id());
+        let _receiver = Receiver::create(test_device_at("list", 2)).unwrap();
+        // The mutex alone doesn't make a device available.
+        let _mutex = Mutex::create_new(&test_device_at("list", 5).mutex).unwrap();
+
+        assert_eq!(available_devices(&base, MAX_DEVICES).unwrap(), [2]);
+        assert!(available_devices(&base, 2).unwrap().is_empty());
+    }
+
+    #[test]
+    fn split_frame_bounds_the_header_and_image() {
+        let names = test_device("split");
+        let mutex = Mutex::create_new(&names.mutex).unwrap();
+        let mapping = FileMapping::<ReadWrite>::create_new(&names.data, HEADER_SIZE + 16).unwrap();
+
+        // SAFETY: the mapping is only accessed while holding the mutex.
+        let mut shared = unsafe { SharedMemory::new(mapping, mutex) };
+
+        shared
+            .with(Duration::MAX, |bytes, _| {
+                let (header, image) = split_frame(bytes);
+                assert_eq!(image.len(), 16);
+
+                header.timeout = -1;
+                image.fill(0xab);
+            })
+            .unwrap();
+
+        shared
+            .with(Duration::MAX, |bytes, _| {
+                let (header, image) = bytes.split_at(HEADER_SIZE);
+                let (rest, timeout) = header.split_at(HEADER_SIZE - size_of::<c_int>());
+
+                assert_eq!(timeout, (-1 as c_int).to_ne_bytes());
+                assert!(rest.iter().all(|&byte| byte == 0));
+                assert!(image.iter().all(|&byte| byte == 0xab));
+            })
+            .unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn split_frame_rejects_misaligned_memory() {
+        let mut storage = [0u32; HEADER_SIZE / 4 + 1];
+
+        // SAFETY: `storage` is valid for `size_of_val(&storage)` bytes, which `u8` can alias.
+        let bytes = unsafe {
+            slice::from_raw_parts_mut(storage.as_mut_ptr().cast::<u8>(), size_of_val(&storage))
+        };
+
+        // `Header` is aligned like `u32`, so this is off by one byte.
+        split_frame(&mut bytes[1..]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn split_frame_rejects_memory_shorter_than_a_header() {
+        split_frame(&mut [0; HEADER_SIZE - 1]);
+    }
+
+    /// A YUY2-like packed format: two bytes per pixel, under a code UnityCapture doesn't use.
+    const YUY2: PixelFormat = PixelFormat::Custom {
+        code: 100,
+        bytes_per_pixel: NonZeroU8::new(2).unwrap(),
+    };
+
+    #[test]
+    fn custom_formats_size_frames_by_their_pixel_size() {
+        let config = FrameConfig::new(640, 480, YUY2).unwrap();
+
+        assert_eq!(config.row_len(), 640 * 2);
+        assert_eq!(config.frame_len(), 640 * 480 * 2);
+
+        let mut header = Header::new();
+        header.fill(config).unwrap();
+
+        assert_eq!(header.format, 100);
+        assert_eq!(header.stride, 640);
+    }
+
+    #[test]
+    fn custom_format_frames_are_sent_with_their_code() {
+        let mut receiver = Receiver::create(test_device("custom-format")).unwrap();
+        let mut sender = sender_for(&receiver);
+        let config = FrameConfig::new(2, 2, YUY2).unwrap();
+
+        // An RGBA-sized frame is too long.
+        assert!(sender.try_send(config, &[7; 2 * 2 * 4]).is_err());
+        assert!(sender.try_send(config, &[7; 2 * 2 * 2]).unwrap());
+
+        // The header doesn't record the pixel size, so receivers can't size the image by it.
+        let snapshot = receiver.wait_frame(Duration::ZERO).unwrap().unwrap();
+        assert_eq!(snapshot.header.format, 100);
+        assert_eq!((snapshot.header.width, snapshot.header.height), (2, 2));
+        assert!(matches!(
+            snapshot.config,
+            Err(InvalidHeaderError::Format { code: 100 })
+        ));
+    }
+
+    #[test]
+    fn header_round_trips_through_read_only_access() {
+        let names = test_device("read-only-header");
+        let mut written = Header::new();
+        written
+            .fill(
+                config()
+                    .with_mirror_mode(MirrorMode::Horizontal)
+                    .with_frame_timeout_ms(500)
+                    .unwrap(),
+            )
+            .unwrap();
+
+        let mutex = Mutex::create_new(&names.mutex).unwrap();
+        let mapping = FileMapping::<ReadWrite>::create_new(&names.data, HEADER_SIZE).unwrap();
+
+        // SAFETY: both views are only accessed while holding the mutex.
+        let mut writer = unsafe { SharedMemory::new(mapping, mutex) };
+
+        writer
+            .with(Duration::MAX, |bytes, _| {
+                bytes.copy_from_slice(written.as_bytes());
+            })
+            .unwrap();
+
+        // SAFETY: the mapping was created with `HEADER_SIZE` bytes.
+        let mapping =
+            unsafe { FileMapping::open_existing_readonly(&names.data, HEADER_SIZE) }.unwrap();
+        let mutex = Mutex::open_existing(&names.mutex).unwrap();
+
+        // SAFETY: both views are only accessed while holding the mutex.
+        let mut reader = unsafe { SharedMemory::new(mapping, mutex) };
+        let read = reader
+            .with_ref(Duration::MAX, |bytes, _| Header::read(bytes).unwrap())
+            .unwrap();
+
+        assert_eq!(read.as_bytes(), written.as_bytes());
+        // The rate limit is never sent, so it's the only thing not recovered.
+        assert_eq!(
+            read.config().unwrap(),
+            config()
+                .with_mirror_mode(MirrorMode::Horizontal)
+                .with_frame_timeout_ms(500)
+                .unwrap()
+        );
+    }
+
+    /// Returns a sender for `receiver`'s device.
+    fn sender_for(receiver: &Receiver) -> Sender {
+        Sender::new(SenderOptions {
+            device: receiver.names().clone(),
+            ..SenderOptions::default()
+        })
+    }
+
+    /// Returns the frame `receiver` was sent since the last call, if any.
+    fn take_frame(receiver: &mut Receiver) -> Option<Vec<u8>> {
+        receiver
+            .wait_frame(Duration::ZERO)
+            .unwrap()
+            .map(|snapshot| snapshot.image)
+    }
+
+    #[test]
+    fn disconnecting_closes_every_handle() {
+        let receiver = Receiver::create(test_device("close")).unwrap();
+        let mut sender = sender_for(&receiver);
+
+        sender.try_connect().unwrap();
+        assert_eq!(sender.connection_state(), ConnectionState::Connected);
+        assert!(Event::exists(&receiver.names().want).unwrap());
+
+        sender.reset();
+
+        assert_eq!(sender.connection_state(), ConnectionState::Disconnected);
+        assert_eq!(sender.last_win32_error(), None);
+        // The sender held the only handle to `WANT`, so closing it destroyed the event.
+        assert!(!Event::exists(&receiver.names().want).unwrap());
+    }
+
+    #[test]
+    fn frames_are_skipped_until_wanted() {
+        let mut receiver = Receiver::create(test_device("skip")).unwrap();
+        let mut sender = sender_for(&receiver);
+        let config = FrameConfig::new(2, 2, PixelFormat::Uint8).unwrap();
+        let frame = [7; 16];
+
+        assert_eq!(
+            sender.try_send_if_wanted(config, &frame).unwrap(),
+            SendOutcome::Skipped
+        );
+        assert_eq!(take_frame(&mut receiver), None);
+        assert_eq!(sender.stats().published, 0);
+
+        receiver.request_frame().unwrap();
+
+        assert_eq!(
+            sender.try_send_if_wanted(config, &frame).unwrap(),
+            SendOutcome::Sent
+        );
+        assert_eq!(take_frame(&mut receiver), Some(frame.to_vec()));
+    }
+
+    #[test]
+    fn frames_that_wont_be_sent_leave_the_request_pending() {
+        let mut receiver = Receiver::create(test_device("pending")).unwrap();
+        let mut sender = sender_for(&receiver);
+        let config = FrameConfig::new(2, 2, PixelFormat::Uint8).unwrap();
+
+        sender.try_connect().unwrap();
+        receiver.request_frame().unwrap();
+
+        assert!(matches!(
+            sender.try_send_if_wanted(config, &[7; 15]),
+            Err(Error::Send {
+                source: SendFrameError::FrameLength { .. }
+            })
+        ));
+
+        sender.set_frame_validator(Some(Box::new(|frame| frame[0] != 0)));
+        assert_eq!(
+            sender.try_send_if_wanted(config, &[0; 16]).unwrap(),
+            SendOutcome::Rejected
+        );
+        assert_eq!(take_frame(&mut receiver), None);
+
+        // Had either frame consumed the request, this one would be skipped.
+        assert_eq!(
+            sender.try_send_if_wanted(config, &[7; 16]).unwrap(),
+            SendOutcome::Sent
+        );
+        assert_eq!(take_frame(&mut receiver), Some(vec![7; 16]));
+    }
+
+    #[test]
+    fn snapshots_show_the_last_frame() {
+        let receiver = Receiver::create(test_device("snapshot")).unwrap();
+        let mut sender = sender_for(&receiver);
+        let config = FrameConfig::new(2, 2, PixelFormat::Uint8)
+            .unwrap()
+            .with_mirror_mode(MirrorMode::Horizontal);
+
+        assert!(sender.try_send(config, &[3; 16]).unwrap());
+
+        let snapshot = read_snapshot(receiver.names(), Duration::MAX).unwrap();
+        assert_eq!(snapshot.config.unwrap(), config);
+        assert_eq!(snapshot.header.stride, 2);
+        assert_eq!(snapshot.image, vec![3; 16]);
+    }
+
+    #[test]
+    fn snapshots_of_an_unwritten_header_are_empty() {
+        let receiver = Receiver::create(test_device("empty-snapshot")).unwrap();
+        let snapshot = read_snapshot(receiver.names(), Duration::MAX).unwrap();
+
+        assert!(matches!(
+            snapshot.config,
+            Err(InvalidHeaderError::Dimensions { .. })
+        ));
+        assert!(snapshot.image.is_empty());
+    }
+
+    /// Locks the mutex named `name` on another thread, since a thread can always lock a mutex
+    /// it owns already, and holds it until the returned sender is dropped.
+    fn hold_mutex(name: &str) -> std::sync::mpsc::Sender<()> {
+        let name = name.to_owned();
+        let (locked_tx, locked_rx) = std::sync::mpsc::channel();
+        let (release_tx, release_rx) = std::sync::mpsc::channel::<()>();
+
+        thread::spawn(move || {
+            let mut mutex = Mutex::open_existing(&name).unwrap();
+
+            mutex
+                .with_lock_timeout(Duration::MAX, |_| {
+                    locked_tx.send(()).unwrap();
+                    // Fails once the other end is dropped.
+                    let _ = release_rx.recv();
+                })
+                .unwrap();
+        });
+
+        locked_rx.recv().unwrap();
+        release_tx
+    }
+
+    /// Locks the mutex named `name` on another thread that exits without releasing it, which
+    /// leaves the mutex abandoned.
+    fn abandon_mutex(name: &str) {
+        use crate::utils::StrExt;
+        use windows::Win32::Foundation::CloseHandle;
+        use windows::Win32::System::Threading::{
+            INFINITE, OpenMutexW, SYNCHRONIZATION_SYNCHRONIZE, WaitForSingleObject,
+        };
+        use windows::core::PCWSTR;
+
+        let name = name.to_u16cstring().unwrap();
+
+        thread::spawn(move || {
+            // SAFETY: `name` is a nul-terminated UTF-16 string, and the handle is valid until
+            // closed. Closing it doesn't release the mutex.
+            unsafe {
+                let handle = OpenMutexW(
+                    SYNCHRONIZATION_SYNCHRONIZE,
+                    false,
+                    PCWSTR::from_raw(name.as_ptr()),
+                )
+                .unwrap();
+
+                WaitForSingleObject(handle, INFINITE);
+                CloseHandle(handle).unwrap();
+            }
+        })
+        .join()
+        .unwrap();
+    }
+
+    /// Overwrites the `max_size` the receiver wrote to the header of `names`.
+    fn overwrite_max_size(names: &DeviceNames, max_size: u32) {
+        // SAFETY: receivers create the mapping with at least `HEADER_SIZE` bytes.
+        let mapping =
+            unsafe { FileMapping::<ReadWrite>::open_existing(&names.data, HEADER_SIZE) }.unwrap();
+        let mutex = Mutex::open_existing(&names.mutex).unwrap();
+
+        // SAFETY: the mapping is only accessed while holding the mutex.
+        let mut shared = unsafe { SharedMemory::new(mapping, mutex) };
+        shared
+            .with(Duration::MAX, |bytes, _| {
+                split_frame(bytes).0.max_size = max_size;
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn mappings_of_an_incompatible_receiver_are_rejected_at_connect() {
+        let receiver = Receiver::create(test_device("incompatible")).unwrap();
+        let mut sender = sender_for(&receiver);
+        overwrite_max_size(receiver.names(), 1920 * 1080 * 4);
+
+        let error = sender.try_connect().unwrap_err();
+        assert!(matches!(
+            error,
+            Error::Init {
+                source: InitError::IncompatibleMapping {
+                    found: 8294400,
+                    expected: MAX_IMAGE_SIZE,
+                }
+            }
+        ));
+        assert_eq!(error.code(), "INIT_INCOMPATIBLE_MAPPING");
+        assert_ne!(sender.connection_state(), ConnectionState::Connected);
+    }
+
+    #[test]
+    fn frames_are_sent_after_an_abandoned_lock_if_the_header_is_intact() {
+        let mut receiver = Receiver::create(test_device("abandoned")).unwrap();
+        let mut sender = sender_for(&receiver);
+        let config = FrameConfig::new(2, 2, PixelFormat::Uint8).unwrap();
+
+        assert!(sender.try_send(config, &[1; 16]).unwrap());
+        assert_eq!(take_frame(&mut receiver), Some(vec![1; 16]));
+
+        abandon_mutex(&receiver.names().mutex);
+
+        assert!(sender.try_send(config, &[2; 16]).unwrap());
+        let snapshot = receiver.wait_frame(Duration::ZERO).unwrap().unwrap();
+        assert_eq!(snapshot.config.unwrap(), config);
+        assert_eq!(snapshot.image, [2; 16]);
+    }
+
+    #[test]
+    fn an_abandoned_lock_over_a_corrupt_header_disconnects() {
+        let receiver = Receiver::create(test_device("abandoned-corrupt")).unwrap();
+        let mut sender = sender_for(&receiver);
+        let config = FrameConfig::new(2, 2, PixelFormat::Uint8).unwrap();
+        sender.try_connect().unwrap();
+
+        overwrite_max_size(receiver.names(), 0);
+        abandon_mutex(&receiver.names().mutex);
+
+        assert!(matches!(
+            sender.try_send(config, &[1; 16]),
+            Err(Error::Send {
+                source: SendFrameError::CorruptHeader { max_size: 0 }
+            })
+        ));
+        assert_eq!(sender.connection_state(), ConnectionState::Disconnected);
+    }
+
+    /// Returns options for `names` that fall back to a pipe nothing listens on.
+    fn options_with_missing_pipe(names: DeviceNames) -> SenderOptions {
+        SenderOptions {
+            device: names,
+            pipe_fallback: Some(format!(
+                r"\\.\pipe\node-vcam-test-{}-missing",
+                std::process::id()
+            )),
+            ..SenderOptions::default()
+        }
+    }
+
+    #[test]
+    fn a_missing_receiver_doesnt_fall_back_to_the_pipe() {
+        let mut sender = Sender::new(options_with_missing_pipe(test_device("no-fallback")));
+
+        assert!(matches!(
+            sender.try_connect(),
+            Err(Error::Init {
+                source: InitError::OpenMutex {
+                    source: OpenMutexError::NotFound { .. }
+                }
+            })
+        ));
+    }
+
+    #[test]
+    fn an_unopenable_mutex_falls_back_to_the_pipe() {
+        let names = test_device("fallback");
+        // An event with the mutex's name makes opening the mutex fail for good.
+        let (_event, _) = Event::create_new(&names.mutex).unwrap();
+        let mut sender = Sender::new(options_with_missing_pipe(names));
+
+        let Err(Error::Init {
+            source: InitError::OpenPipe { shared_memory, .. },
+        }) = sender.try_connect()
+        else {
+            panic!("expected the pipe to be tried");
+        };
+
+        assert!(matches!(
+            *shared_memory,
+            InitError::OpenMutex {
+                source: OpenMutexError::Os { .. }
+            }
+        ));
+    }
+
+    #[test]
+    fn failed_connects_record_the_win32_error() {
+        use windows::Win32::Foundation::ERROR_INVALID_HANDLE;
+
+        let names = test_device("win32-error");
+        // Opening a mutex whose name belongs to an event fails with `ERROR_INVALID_HANDLE`.
+        let (_event, _) = Event::create_new(&names.mutex).unwrap();
+        let mut sender = Sender::new(SenderOptions {
+            device: names,
+            ..SenderOptions::default()
+        });
+
+        assert!(sender.try_connect().is_err());
+        assert_eq!(sender.last_win32_error(), Some(ERROR_INVALID_HANDLE.0));
+
+        sender.reset_state();
+        assert_eq!(sender.last_win32_error(), None);
+    }
+
+    #[test]
+    fn connecting_gives_up_on_a_held_mutex() {
+        let receiver = Receiver::create(test_device("held-at-connect")).unwrap();
+        let mut sender = Sender::new(SenderOptions {
+            device: receiver.names().clone(),
+            lock_timeout: Duration::from_millis(50),
+            ..SenderOptions::default()
+        });
+
+        let holder = hold_mutex(&receiver.names().mutex);
+        let start = Instant::now();
+
+        assert!(matches!(
+            sender.try_connect(),
+            Err(Error::Init {
+                source: InitError::LockMutex {
+                    source: LockMutexError::Timeout
+                }
+            })
+        ));
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        drop(holder);
+        thread::sleep(Duration::from_millis(50));
+        sender.try_connect().unwrap();
+    }
+
+    #[test]
+    fn sending_gives_up_on_a_held_mutex() {
+        let receiver = Receiver::create(test_device("held-at-send")).unwrap();
+        let mut sender = Sender::new(SenderOptions {
+            device: receiver.names().clone(),
+            lock_timeout: Duration::from_millis(50),
+            ..SenderOptions::default()
+        });
+        let config = FrameConfig::new(2, 2, PixelFormat::Uint8).unwrap();
+
+        sender.try_connect().unwrap();
+        let _holder = hold_mutex(&receiver.names().mutex);
+
+        let error = sender.try_send(config, &[1; 16]).unwrap_err();
+        assert!(matches!(
+            error,
+            Error::Send {
+                source: SendFrameError::LockMutex {
+                    source: LockMutexError::Timeout
+                }
+            }
+        ));
+        assert_eq!(error.code(), "SEND_LOCK_TIMEOUT");
+        assert_eq!(sender.stats().lock_timeouts, 1);
+        // A timeout doesn't mean the receiver went away.
+        assert_eq!(sender.connection_state(), ConnectionState::Connected);
+    }
+
+    #[test]
+    fn keep_alive_resignals_the_last_frame() {
+        let mut receiver = Receiver::create(test_device("keep-alive")).unwrap();
+        let mut sender = sender_for(&receiver);
+        let config = FrameConfig::new(2, 2, PixelFormat::Uint8).unwrap();
+
+        let no_image = |result| {
+            matches!(
+                result,
+                Err(Error::Send {
+                    source: SendFrameError::NoImage
+                })
+            )
+        };
+
+        assert!(no_image(sender.try_keep_alive()));
+        sender.try_connect().unwrap();
+        assert!(no_image(sender.try_keep_alive()));
+        assert_eq!(take_frame(&mut receiver), None);
+
+        assert!(sender.try_send(config, &[1; 16]).unwrap());
+        assert_eq!(take_frame(&mut receiver), Some(vec![1; 16]));
+
+        sender.try_keep_alive().unwrap();
+        assert_eq!(take_frame(&mut receiver), Some(vec![1; 16]));
+        assert_eq!(sender.stats().published, 1);
+    }
+
+    #[test]
+    fn stats_count_every_outcome_until_reset() {
+        let names = test_device("stats");
+        let mut sender = Sender::new(SenderOptions {
+            device: names.clone(),
+            want_timeout: Some(Duration::ZERO),
+            lock_timeout: Duration::from_millis(50),
+            ..SenderOptions::default()
+        });
+        let config = FrameConfig::new(2, 2, PixelFormat::Uint8).unwrap();
+
+        assert!(sender.try_send(config, &[1; 16]).is_err());
+        assert!(sender.try_send(config, &[1; 16]).is_err());
+        assert_eq!(sender.stats().init_failures, 2);
+
+        let _receiver = Receiver::create(names.clone()).unwrap();
+
+        // Nothing asks for frames, so waiting for `WANT` times out every time.
+        assert!(sender.try_send(config, &[1; 16]).unwrap());
+        assert!(sender.try_send(config, &[1; 16]).unwrap());
+
+        // A frame that isn't published doesn't count its `WANT` timeout.
+        let holder = hold_mutex(&names.mutex);
+        assert!(sender.try_send(config, &[1; 16]).is_err());
+        drop(holder);
+
+        let stats = sender.stats();
+        assert_eq!(stats.published, 2);
+        assert_eq!(stats.want_timeouts, 2);
+        assert_eq!(stats.lock_timeouts, 1);
+        assert_eq!(stats.init_failures, 2);
+
+        sender.reset_stats();
+
+        let stats = sender.stats();
+        assert_eq!(stats.published, 0);
+        assert_eq!(stats.want_timeouts, 0);
+        assert_eq!(stats.lock_timeouts, 0);
+        assert_eq!(stats.init_failures, 0);
+    }
+
+    #[test]
+    fn batches_are_presented_in_order_when_paced_by_the_receiver() {
+        let receiver = Receiver::create(test_device("batch")).unwrap();
+        let mut sender = Sender::new(SenderOptions {
+            device: receiver.names().clone(),
+            want_timeout: Some(Duration::from_secs(5)),
+            ..SenderOptions::default()
+        });
+        let config = FrameConfig::new(2, 2, PixelFormat::Uint8).unwrap();
+        sender.try_connect().unwrap();
+
+        let consumer = thread::spawn(move || {
+            let mut receiver = receiver;
+            let mut frames = Vec::new();
+
+            for _ in 0..3 {
+                receiver.request_frame().unwrap();
+
+                let snapshot = receiver.wait_frame(Duration::from_secs(5)).unwrap();
+                frames.push(snapshot.unwrap().image);
+            }
+
+            frames
+        });
+
+        let (sent, result) = sender.try_send_batch(config, &[&[1; 16], &[2; 16], &[3; 16]]);
+        result.unwrap();
+        assert_eq!(sent, 3);
+
+        assert_eq!(
+            consumer.join().unwrap(),
+            [vec![1; 16], vec![2; 16], vec![3; 16]]
+        );
+    }
+
+    #[test]
+    fn short_frames_are_rejected_unless_padded() {
+        let mut receiver = Receiver::create(test_device("short-frame")).unwrap();
+        let mut sender = sender_for(&receiver);
+        let config = FrameConfig::new(2, 2, PixelFormat::Uint8).unwrap();
+
+        assert!(sender.try_send(config, &[1; 16]).unwrap());
+        assert_eq!(take_frame(&mut receiver), Some(vec![1; 16]));
+
+        assert!(matches!(
+            sender.try_send(config, &[2; 8]),
+            Err(Error::Send {
+                source: SendFrameError::FrameLength {
+                    expected: 16,
+                    actual: 8
+                }
+            })
+        ));
+        assert_eq!(take_frame(&mut receiver), None);
+
+        sender.set_options(SenderOptions {
+            device: receiver.names().clone(),
+            pad_short_frames: true,
+            ..SenderOptions::default()
+        });
+
+        // No stale pixels of the previous frame are left behind.
+        assert!(sender.try_send(config, &[2; 8]).unwrap());
+        let mut padded = vec![2; 8];
+        padded.resize(16, 0);
+        assert_eq!(take_frame(&mut receiver), Some(padded));
+
+        assert!(sender.try_send(config, &[3; 16]).unwrap());
+        assert_eq!(take_frame(&mut receiver), Some(vec![3; 16]));
+
+        // Padding doesn't make long frames acceptable.
+        assert!(sender.try_send(config, &[4; 20]).is_err());
+    }
+
+    #[test]
+    fn resetting_after_an_error_reconnects_on_the_next_frame() {
+        let mut receiver = Receiver::create(test_device("reset")).unwrap();
+        let mut sender = Sender::new(SenderOptions {
+            device: receiver.names().clone(),
+            lock_timeout: Duration::from_millis(50),
+            ..SenderOptions::default()
+        });
+        let config = FrameConfig::new(2, 2, PixelFormat::Uint8).unwrap();
+
+        sender.try_connect().unwrap();
+        let holder = hold_mutex(&receiver.names().mutex);
+        assert!(sender.try_send(config, &[1; 16]).is_err());
+        assert_eq!(sender.connection_state(), ConnectionState::Connected);
+        drop(holder);
+        thread::sleep(Duration::from_millis(50));
+
+        sender.reset();
+        assert_eq!(sender.connection_state(), ConnectionState::Disconnected);
+        assert_eq!(sender.stats().lock_timeouts, 1);
+
+        assert!(sender.try_send(config, &[2; 16]).unwrap());
+        assert_eq!(sender.connection_state(), ConnectionState::Connected);
+        assert_eq!(take_frame(&mut receiver), Some(vec![2; 16]));
+    }
+
+    #[test]
+    fn changing_the_device_mid_stream_reconnects_to_it() {
+        let mut first = Receiver::create(test_device_at("switch", 0)).unwrap();
+        let mut second = Receiver::create(test_device_at("switch", 1)).unwrap();
+        let mut sender = sender_for(&first);
+        let config = FrameConfig::new(2, 2, PixelFormat::Uint8).unwrap();
+
+        let states = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let listener_states = states.clone();
+        sender.set_state_listener(Some(Box::new(move |state| {
+            listener_states.lock().unwrap().push(state);
+        })));
+
+        assert!(sender.try_send(config, &[1; 16]).unwrap());
+        assert_eq!(take_frame(&mut first), Some(vec![1; 16]));
+
+        sender.set_options(SenderOptions {
+            device: second.names().clone(),
+            ..SenderOptions::default()
+        });
+
+        assert_eq!(sender.connection_state(), ConnectionState::Disconnected);
+        assert_eq!(
+            states.lock().unwrap().last(),
+            Some(&ConnectionState::Disconnected)
+        );
+
+        assert!(sender.try_send(config, &[2; 16]).unwrap());
+        assert_eq!(take_frame(&mut second), Some(vec![2; 16]));
+        assert_eq!(take_frame(&mut first), None);
+    }
+
+    #[test]
+    fn every_sent_signal_wakes_a_waiting_receiver() {
+        let config = FrameConfig::new(2, 2, PixelFormat::Uint8).unwrap();
+
+        for (name, sent_signal) in [
+            ("set", SentSignal::Set),
+            ("pulse", SentSignal::Pulse),
+            ("set-then-reset", SentSignal::SetThenReset),
+        ] {
+            let receiver = Receiver::create(test_device(&format!("sent-{name}"))).unwrap();
+            let mut sender = Sender::new(SenderOptions {
+                device: receiver.names().clone(),
+                sent_signal,
+                ..SenderOptions::default()
+            });
+            sender.try_connect().unwrap();
+
+            let (woken, wakes) = std::sync::mpsc::channel();
+            let waiter = thread::spawn(move || {
+                let mut receiver = receiver;
+                let frame = receiver.wait_frame(Duration::from_secs(5)).unwrap();
+                woken.send(()).unwrap();
+                (receiver, frame.map(|snapshot| snapshot.image))
+            });
+
+            // A pulse only wakes a receiver that is waiting already, so keep sending until it is.
+            let start = Instant::now();
+
+            while wakes.try_recv().is_err() && start.elapsed() < Duration::from_secs(5) {
+                sender.try_send(config, &[1; 16]).unwrap();
+                thread::sleep(Duration::from_millis(10));
+            }
+
+            let (mut receiver, frame) = waiter.join().unwrap();
+            assert_eq!(frame, Some(vec![1; 16]), "{sent_signal:?}");
+
+            // Only `Set` leaves `SENT` signaled for a receiver that wasn't waiting.
+            sender.try_send(config, &[2; 16]).unwrap();
+            assert_eq!(
+                take_frame(&mut receiver).is_some(),
+                sent_signal == SentSignal::Set,
+                "{sent_signal:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn frames_wait_for_the_receiver_to_ask() {
+        let mut receiver = Receiver::create(test_device("wait-want")).unwrap();
+        let mut sender = Sender::new(SenderOptions {
+            device: receiver.names().clone(),
+            want_timeout: Some(Duration::from_secs(5)),
+            ..SenderOptions::default()
+        });
+        let config = FrameConfig::new(2, 2, PixelFormat::Uint8).unwrap();
+        sender.try_connect().unwrap();
+
+        let want = receiver.names().want.clone();
+        let start = Instant::now();
+        let requester = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(100));
+            Event::open_existing(&want).unwrap().set().unwrap();
+        });
+
+        assert!(sender.try_send(config, &[1; 16]).unwrap());
+        assert!(start.elapsed() >= Duration::from_millis(90));
+        assert_eq!(take_frame(&mut receiver), Some(vec![1; 16]));
+        assert_eq!(sender.stats().want_timeouts, 0);
+
+        requester.join().unwrap();
+    }
+
+    #[test]
+    fn frames_are_sent_anyway_when_the_receiver_doesnt_ask() {
+        let mut receiver = Receiver::create(test_device("want-timeout")).unwrap();
+        let mut sender = Sender::new(SenderOptions {
+            device: receiver.names().clone(),
+            want_timeout: Some(Duration::from_millis(50)),
+            ..SenderOptions::default()
+        });
+        let config = FrameConfig::new(2, 2, PixelFormat::Uint8).unwrap();
+
+        assert!(sender.try_send(config, &[1; 16]).unwrap());
+        assert_eq!(take_frame(&mut receiver), Some(vec![1; 16]));
+        assert_eq!(sender.stats().want_timeouts, 1);
+    }
+
+    #[test]
+    fn requests_before_the_frame_dont_confirm_it() {
+        let mut receiver = Receiver::create(test_device("stale-confirm")).unwrap();
+        let mut sender = sender_for(&receiver);
+        let config = FrameConfig::new(2, 2, PixelFormat::Uint8).unwrap();
+
+        sender.try_connect().unwrap();
+        receiver.request_frame().unwrap();
+
+        let confirmation = sender
+            .try_send_and_confirm(config, &[1; 16], Duration::from_millis(50))
+            .unwrap();
+
+        assert!(confirmation.published);
+        assert!(!confirmation.confirmed);
+        assert!(confirmation.waited >= Duration::from_millis(40));
+        assert_eq!(take_frame(&mut receiver), Some(vec![1; 16]));
+    }
+
+    #[test]
+    fn the_next_request_confirms_the_frame() {
+        let receiver = Receiver::create(test_device("confirm")).unwrap();
+        let mut sender = sender_for(&receiver);
+        let config = FrameConfig::new(2, 2, PixelFormat::Uint8).unwrap();
+        sender.try_connect().unwrap();
+
+        let consumer = thread::spawn(move || {
+            let mut receiver = receiver;
+            let frame = receiver.wait_frame(Duration::from_secs(5)).unwrap();
+            receiver.request_frame().unwrap();
+            frame.map(|snapshot| snapshot.image)
+        });
+
+        let confirmation = sender
+            .try_send_and_confirm(config, &[1; 16], Duration::from_secs(5))
+            .unwrap();
+
+        assert!(confirmation.published);
+        assert!(confirmation.confirmed);
+        assert_eq!(consumer.join().unwrap(), Some(vec![1; 16]));
+    }
+
+    #[test]
+    fn partially_opened_objects_are_kept_until_reset() {
+        let names = test_device("partial-init");
+        let _mutex = Mutex::create_new(&names.mutex).unwrap();
+        let options = SenderOptions {
+            device: names.clone(),
+            ..SenderOptions::default()
+        };
+        let mut uninit = Uninit::default();
+
+        // Without `SENT`, connecting fails after opening the mutex and creating `WANT`.
+        assert!(matches!(
+            uninit.try_init(&options),
+            Err(InitError::OpenSentEvent { .. })
+        ));
+        assert!(uninit.mutex.is_some());
+        assert!(uninit.want_frame.is_some());
+        assert!(uninit.sent_frame.is_none());
+        assert!(Event::exists(&names.want).unwrap());
+
+        uninit.reset();
+
+        assert!(uninit.mutex.is_none());
+        assert!(uninit.want_frame.is_none());
+        assert!(!Event::exists(&names.want).unwrap());
+    }
+
+    #[test]
+    fn the_first_frame_after_reconnecting_fills_the_header() {
+        let names = test_device("refill-header");
+        let config = FrameConfig::new(2, 2, PixelFormat::Uint8).unwrap();
+        let mut sender = Sender::new(SenderOptions {
+            device: names.clone(),
+            ..SenderOptions::default()
+        });
+
+        let receiver = Receiver::create(names.clone()).unwrap();
+        assert!(sender.try_send(config, &[1; 16]).unwrap());
+        sender.reset();
+        drop(receiver);
+
+        // The restarted receiver starts with a blank header, while the config stays the same.
+        let mut receiver = Receiver::create(names).unwrap();
+        assert!(sender.try_send(config, &[2; 16]).unwrap());
+
+        let snapshot = receiver.wait_frame(Duration::ZERO).unwrap().unwrap();
+        assert_eq!(snapshot.config.unwrap(), config);
+        assert_eq!(snapshot.image, [2; 16]);
+    }
+}
@@ -12,10 +12,14 @@ impl<T> OptionExt<T> for Option<T> {
     where
         F: FnOnce() -> Result<T, E>,
     {
-        if let None = self {
+        if self.is_none() {
             *self = Some(f()?);
         }
 
+        debug_assert!(self.is_some());
+
+        // SAFETY: `self` is `Some`: it either already was, or `f` succeeded and its value was
+        // just stored. If `f` fails or panics, we never get here.
         Ok(unsafe { self.as_mut().unwrap_unchecked() })
     }
 }
@@ -41,8 +45,49 @@ pub trait StrExt {
 
 impl StrExt for str {
     fn to_u16cstring(&self) -> Result<U16CString, ToUC16StringError> {
-        U16CString::from_str(&self)
+        U16CString::from_str(self)
             .context(contains_nul_error::ContainsNulSnafu)
             .context(to_uc16_string_error::ToUC16StringSnafu)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn try_get_or_insert_with_inserts_into_none() {
+        let mut option = None;
+
+        *option.try_get_or_insert_with(|| Ok::<_, ()>(1)).unwrap() += 1;
+        assert_eq!(option, Some(2));
+    }
+
+    #[test]
+    fn try_get_or_insert_with_keeps_a_present_value() {
+        let mut option = Some(1);
+
+        let value = option
+            .try_get_or_insert_with(|| -> Result<i32, ()> { panic!("must not be called") })
+            .unwrap();
+
+        assert_eq!(*value, 1);
+    }
+
+    #[test]
+    fn try_get_or_insert_with_leaves_none_on_error() {
+        let mut option: Option<i32> = None;
+
+        assert_eq!(
+            option.try_get_or_insert_with(|| Err("failed")),
+            Err("failed")
+        );
+        assert_eq!(option, None);
+    }
+
+    #[test]
+    fn to_u16cstring_rejects_nul_bytes() {
+        assert_eq!("abc".to_u16cstring().unwrap().to_string_lossy(), "abc");
+        assert!("a\0c".to_u16cstring().is_err());
+    }
+}
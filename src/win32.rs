@@ -1,15 +1,28 @@
 use snafu::prelude::*;
 use std::marker::PhantomData;
+use std::mem::ManuallyDrop;
 use std::ptr::NonNull;
 use std::slice;
+use std::time::Duration;
 use windows::Win32::Foundation::HANDLE;
-use windows::core::PCWSTR;
+use windows::core::{HRESULT, PCWSTR};
 
 use crate::utils::{StrExt, ToUC16StringError};
 
 // Generic Windows API error type.
 type Win32Error = windows::core::Error;
 
+/// Returns the Win32 error code (as returned by `GetLastError`) of the first OS error in the
+/// source chain of `e`, if any.
+pub fn win32_error_code(e: &(dyn std::error::Error + 'static)) -> Option<u32> {
+    use windows::Win32::Foundation::WIN32_ERROR;
+
+    std::iter::successors(Some(e), |e| e.source())
+        .find_map(|e| e.downcast_ref::<Win32Error>())
+        .and_then(WIN32_ERROR::from_error)
+        .map(|code| code.0)
+}
+
 /// Represents an owned object handle.
 // SAFETY: always owns a valid handle.
 #[derive(Debug)]
@@ -27,6 +40,17 @@ impl Handle {
     unsafe fn new(handle: HANDLE) -> Handle {
         Handle(handle)
     }
+
+    /// Closes the handle, reporting a failure instead of just logging it like dropping does.
+    fn close(self) -> Result<(), Win32Error> {
+        use windows::Win32::Foundation::CloseHandle;
+
+        let handle = ManuallyDrop::new(self);
+
+        // SAFETY: `Handle` always contains a valid handle, and `handle` is never dropped, so
+        // it isn't closed twice.
+        unsafe { CloseHandle(handle.0) }
+    }
 }
 
 impl Drop for Handle {
@@ -38,6 +62,48 @@ impl Drop for Handle {
     }
 }
 
+#[derive(Debug, Snafu)]
+#[snafu(display("failed to close the handle"))]
+pub struct CloseError {
+    source: Win32Error,
+}
+
+#[derive(Debug, Snafu)]
+#[snafu(module)]
+pub enum ExistsError {
+    #[snafu(display("invalid object name: `{name}`"))]
+    InvalidName {
+        source: ToUC16StringError,
+        name: String,
+    },
+
+    #[snafu(display("access to `{name}` was denied"))]
+    AccessDenied { name: String },
+
+    #[snafu(display("failed to check whether `{name}` exists"))]
+    Os { source: Win32Error, name: String },
+}
+
+/// Interprets the result of opening `name` with minimal access to check whether it exists.
+///
+/// A successfully opened handle is closed immediately.
+fn check_exists(name: &str, result: windows::core::Result<HANDLE>) -> Result<bool, ExistsError> {
+    use windows::Win32::Foundation::{ERROR_ACCESS_DENIED, ERROR_FILE_NOT_FOUND};
+
+    match result {
+        Ok(raw_handle) => {
+            // SAFETY: the `Open*` functions always return a valid handle on success.
+            drop(unsafe { Handle::new(raw_handle) });
+            Ok(true)
+        }
+        Err(e) if e.code() == HRESULT::from_win32(ERROR_FILE_NOT_FOUND.0) => Ok(false),
+        Err(e) if e.code() == HRESULT::from_win32(ERROR_ACCESS_DENIED.0) => {
+            exists_error::AccessDeniedSnafu { name }.fail()
+        }
+        Err(e) => Err(e).context(exists_error::OsSnafu { name }),
+    }
+}
+
 #[derive(Debug, Snafu)]
 #[snafu(module)]
 pub enum OpenMutexError {
@@ -47,10 +113,29 @@ pub enum OpenMutexError {
         name: String,
     },
 
+    /// No mutex named `name` exists, i.e. nothing has created it (yet).
+    #[snafu(display("no mutex named `{name}` exists"))]
+    NotFound { name: String },
+
     #[snafu(display("failed to open an existing mutex (`{name}`)"))]
     Os { source: Win32Error, name: String },
 }
 
+impl OpenMutexError {
+    /// Classifies the error `OpenMutexW` failed with while opening `name`.
+    pub(crate) fn from_os(source: Win32Error, name: &str) -> OpenMutexError {
+        use windows::Win32::Foundation::ERROR_FILE_NOT_FOUND;
+
+        let name = name.to_owned();
+
+        if source.code() == HRESULT::from_win32(ERROR_FILE_NOT_FOUND.0) {
+            OpenMutexError::NotFound { name }
+        } else {
+            OpenMutexError::Os { source, name }
+        }
+    }
+}
+
 #[derive(Debug, Snafu)]
 #[snafu(module)]
 pub enum LockMutexError {
@@ -61,6 +146,19 @@ pub enum LockMutexError {
     Os { source: Win32Error },
 }
 
+#[derive(Debug, Snafu)]
+#[snafu(module)]
+pub enum CreateMutexError {
+    #[snafu(display("invalid mutex name: `{name}`"))]
+    InvalidName {
+        source: ToUC16StringError,
+        name: String,
+    },
+
+    #[snafu(display("failed to create a mutex (`{name}`)"))]
+    Os { source: Win32Error, name: String },
+}
+
 /// Represents a Win32 mutex.
 #[derive(Debug)]
 pub struct Mutex {
@@ -68,6 +166,30 @@ pub struct Mutex {
 }
 
 impl Mutex {
+    /// Creates a new mutex, not owned by anyone, or opens it if it already exists.
+    pub fn create_new(name: &str) -> Result<Self, CreateMutexError> {
+        use windows::Win32::System::Threading::CreateMutexW;
+
+        let name_wide =
+            name.to_u16cstring()
+                .with_context(|_| create_mutex_error::InvalidNameSnafu {
+                    name: name.to_owned(),
+                })?;
+
+        // SAFETY:
+        // - Creating a mutex is always safe.
+        // - `name_wide` is a nul-terminated UTF-16 string.
+        let raw_handle = unsafe { CreateMutexW(None, false, PCWSTR::from_raw(name_wide.as_ptr())) }
+            .with_context(|_| create_mutex_error::OsSnafu {
+                name: name.to_owned(),
+            })?;
+
+        // SAFETY: `CreateMutexW` always returns a valid handle on success.
+        let handle = unsafe { Handle::new(raw_handle) };
+
+        Ok(Mutex { handle })
+    }
+
     /// Opens an existing mutex.
     pub fn open_existing(name: &str) -> Result<Self, OpenMutexError> {
         use windows::Win32::System::Threading::{OpenMutexW, SYNCHRONIZATION_SYNCHRONIZE};
@@ -88,9 +210,7 @@ impl Mutex {
                 PCWSTR::from_raw(name_wide.as_ptr()),
             )
         }
-        .with_context(|_| open_mutex_error::OsSnafu {
-            name: name.to_owned(),
-        })?;
+        .map_err(|e| OpenMutexError::from_os(e, name))?;
 
         // SAFETY: `OpenMutexW` always returns a valid handle on success.
         let handle = unsafe { Handle::new(raw_handle) };
@@ -98,29 +218,69 @@ impl Mutex {
         Ok(Mutex { handle })
     }
 
+    /// Checks whether a mutex named `name` exists, without keeping it open.
+    pub(crate) fn exists(name: &str) -> Result<bool, ExistsError> {
+        use windows::Win32::System::Threading::{OpenMutexW, SYNCHRONIZATION_SYNCHRONIZE};
+
+        let name_wide = name
+            .to_u16cstring()
+            .context(exists_error::InvalidNameSnafu { name })?;
+
+        // SAFETY:
+        // - Opening a mutex is always safe.
+        // - `name_wide` is a nul-terminated UTF-16 string.
+        let result = unsafe {
+            OpenMutexW(
+                SYNCHRONIZATION_SYNCHRONIZE,
+                false,
+                PCWSTR::from_raw(name_wide.as_ptr()),
+            )
+        };
+
+        check_exists(name, result)
+    }
+
     /// Acquires the mutex lock, blocking the current thread until it is available or the timeout elapses.
-    fn lock(&'_ mut self) -> Result<MutexGuard<'_>, LockMutexError> {
+    fn lock(&'_ mut self, timeout: Duration) -> Result<MutexGuard<'_>, LockMutexError> {
         // SAFETY: `self.handle` refers to a valid mutex.
-        let result = unsafe { wait_for_single_object(self.handle.0) };
+        let result = unsafe { wait_for_single_object(self.handle.0, timeout) };
 
         match result {
-            WaitSingle::Object0 | WaitSingle::Abandoned => Ok(MutexGuard { mutex: self }),
+            WaitSingle::Object0 => Ok(MutexGuard {
+                mutex: self,
+                abandoned: false,
+            }),
+            WaitSingle::Abandoned => Ok(MutexGuard {
+                mutex: self,
+                abandoned: true,
+            }),
             WaitSingle::Timeout => Err(LockMutexError::Timeout),
             WaitSingle::Failed(err) => Err(err).context(lock_mutex_error::OsSnafu),
         }
     }
 
-    pub fn with_lock<F, B>(&mut self, f: F) -> Result<B, LockMutexError>
+    /// Runs `f` while holding the mutex, giving up with [`LockMutexError::Timeout`] if the
+    /// mutex can't be acquired within `timeout`.
+    ///
+    /// `f` is told whether the mutex was abandoned, i.e. its previous owner exited without
+    /// releasing it, in which case the state it protects may be inconsistent.
+    pub fn with_lock_timeout<F, B>(&mut self, timeout: Duration, f: F) -> Result<B, LockMutexError>
     where
-        F: FnOnce() -> B,
+        F: FnOnce(bool) -> B,
     {
-        let _guard = self.lock()?;
-        Ok(f())
+        let guard = self.lock(timeout)?;
+        Ok(f(guard.abandoned))
+    }
+
+    /// Closes the mutex, reporting a failure instead of just logging it like dropping does.
+    pub fn close(self) -> Result<(), CloseError> {
+        self.handle.close().context(CloseSnafu)
     }
 }
 
 struct MutexGuard<'a> {
     mutex: &'a mut Mutex,
+    abandoned: bool,
 }
 
 impl Drop for MutexGuard<'_> {
@@ -164,6 +324,12 @@ pub struct SetEventError {
     source: Win32Error,
 }
 
+#[derive(Debug, Snafu)]
+#[snafu(display("failed to reset the event"))]
+pub struct ResetEventError {
+    source: Win32Error,
+}
+
 #[derive(Debug, Snafu)]
 #[snafu(module)]
 pub enum WaitEventError {
@@ -181,8 +347,12 @@ pub struct Event {
 }
 
 impl Event {
-    /// Creates a new event object.
-    pub fn create_new(name: &str) -> Result<Self, CreateEventError> {
+    /// Creates a new auto-reset event object, or opens it if it already exists. Also returns
+    /// whether it already existed.
+    ///
+    /// Auto-reset events are reset as soon as a single wait on them completes.
+    pub fn create_new(name: &str) -> Result<(Self, bool), CreateEventError> {
+        use windows::Win32::Foundation::{ERROR_ALREADY_EXISTS, GetLastError};
         use windows::Win32::System::Threading::CreateEventW;
 
         let name_wide =
@@ -200,15 +370,32 @@ impl Event {
                     name: name.to_owned(),
                 })?;
 
+        // SAFETY: reading the calling thread's last error code is always safe.
+        let already_exists = unsafe { GetLastError() } == ERROR_ALREADY_EXISTS;
+
         // SAFETY: `CreateEventW` always returns a valid handle on success.
         let handle = unsafe { Handle::new(raw_handle) };
 
-        Ok(Event { handle })
+        Ok((Event { handle }, already_exists))
     }
 
     /// Opens an existing event object.
     pub fn open_existing(name: &str) -> Result<Self, OpenEventError> {
-        use windows::Win32::System::Threading::{EVENT_MODIFY_STATE, OpenEventW};
+        use windows::Win32::System::Threading::EVENT_MODIFY_STATE;
+        Self::open_existing_with_access(name, EVENT_MODIFY_STATE)
+    }
+
+    /// Opens an existing event object so that it can be waited on.
+    pub fn open_existing_waitable(name: &str) -> Result<Self, OpenEventError> {
+        use windows::Win32::System::Threading::SYNCHRONIZATION_SYNCHRONIZE;
+        Self::open_existing_with_access(name, SYNCHRONIZATION_SYNCHRONIZE)
+    }
+
+    fn open_existing_with_access(
+        name: &str,
+        access: windows::Win32::System::Threading::SYNCHRONIZATION_ACCESS_RIGHTS,
+    ) -> Result<Self, OpenEventError> {
+        use windows::Win32::System::Threading::OpenEventW;
 
         let name_wide =
             name.to_u16cstring()
@@ -219,16 +406,10 @@ impl Event {
         // SAFETY:
         // - Opening an existing event object always safe.
         // - `name_wide` is a nul-terminated UTF-16 string.
-        let raw_handle = unsafe {
-            OpenEventW(
-                EVENT_MODIFY_STATE,
-                false,
-                PCWSTR::from_raw(name_wide.as_ptr()),
-            )
-        }
-        .with_context(|_| open_event_error::OsSnafu {
-            name: name.to_owned(),
-        })?;
+        let raw_handle = unsafe { OpenEventW(access, false, PCWSTR::from_raw(name_wide.as_ptr())) }
+            .with_context(|_| open_event_error::OsSnafu {
+                name: name.to_owned(),
+            })?;
 
         // SAFETY: `CreateEventW` always returns a valid handle on success.
         let handle = unsafe { Handle::new(raw_handle) };
@@ -236,6 +417,28 @@ impl Event {
         Ok(Event { handle })
     }
 
+    /// Checks whether an event object named `name` exists, without keeping it open.
+    pub(crate) fn exists(name: &str) -> Result<bool, ExistsError> {
+        use windows::Win32::System::Threading::{OpenEventW, SYNCHRONIZATION_SYNCHRONIZE};
+
+        let name_wide = name
+            .to_u16cstring()
+            .context(exists_error::InvalidNameSnafu { name })?;
+
+        // SAFETY:
+        // - Opening an existing event object always safe.
+        // - `name_wide` is a nul-terminated UTF-16 string.
+        let result = unsafe {
+            OpenEventW(
+                SYNCHRONIZATION_SYNCHRONIZE,
+                false,
+                PCWSTR::from_raw(name_wide.as_ptr()),
+            )
+        };
+
+        check_exists(name, result)
+    }
+
     /// Sets (signals) this event object.
     pub fn set(&self) -> Result<(), SetEventError> {
         use windows::Win32::System::Threading::SetEvent;
@@ -244,10 +447,30 @@ impl Event {
         Ok(())
     }
 
-    /// Blocks the current thread until this event is signaled.
-    pub fn wait(&self) -> Result<(), WaitEventError> {
+    /// Resets (unsignals) this event object.
+    pub fn reset(&self) -> Result<(), ResetEventError> {
+        use windows::Win32::System::Threading::ResetEvent;
+        // SAFETY: resetting an event object is always safe.
+        unsafe { ResetEvent(self.handle.0) }.context(ResetEventSnafu)?;
+        Ok(())
+    }
+
+    /// Sets this event object and resets it right away, releasing the threads that are waiting
+    /// on it at that moment (`PulseEvent`).
+    ///
+    /// `PulseEvent` is unreliable: a waiter that is momentarily not waiting (e.g. handling a
+    /// kernel-mode APC) misses the pulse.
+    pub fn pulse(&self) -> Result<(), SetEventError> {
+        use windows::Win32::System::Threading::PulseEvent;
+        // SAFETY: pulsing an event object is always safe.
+        unsafe { PulseEvent(self.handle.0) }.context(SetEventSnafu)?;
+        Ok(())
+    }
+
+    /// Blocks the current thread until this event is signaled or `timeout` elapses.
+    pub fn wait_timeout(&self, timeout: Duration) -> Result<(), WaitEventError> {
         // SAFETY: waiting an event object is always safe.
-        let result = unsafe { wait_for_single_object(self.handle.0) };
+        let result = unsafe { wait_for_single_object(self.handle.0, timeout) };
 
         match result {
             WaitSingle::Object0 => Ok(()),
@@ -256,6 +479,11 @@ impl Event {
             WaitSingle::Abandoned => unreachable!(),
         }
     }
+
+    /// Closes the event, reporting a failure instead of just logging it like dropping does.
+    pub fn close(self) -> Result<(), CloseError> {
+        self.handle.close().context(CloseSnafu)
+    }
 }
 
 #[derive(Debug, Snafu)]
@@ -274,29 +502,158 @@ pub enum OpenFileMappingError {
     Map { source: Win32Error },
 }
 
-/// Represents a file mapping object.
+#[derive(Debug, Snafu)]
+#[snafu(module)]
+pub enum CreateFileMappingError {
+    #[snafu(display("invalid file mapping name: `{name}`"))]
+    InvalidName {
+        source: ToUC16StringError,
+        name: String,
+    },
+
+    /// Its size is unknown, so it can't be mapped safely.
+    #[snafu(display("a file mapping named `{name}` already exists"))]
+    AlreadyExists { name: String },
+
+    #[snafu(display("failed to create a file mapping (`{name}`)"))]
+    Create { source: Win32Error, name: String },
+
+    #[snafu(display("failed to map a view of the file mapping"))]
+    Map { source: Win32Error },
+}
+
+/// The access a [`FileMapping`] view is mapped with.
+pub trait Access {
+    const FILE_MAP: windows::Win32::System::Memory::FILE_MAP;
+}
+
+/// Read-only access (`FILE_MAP_READ`), e.g. for inspecting what the receiver sees. Such a
+/// mapping is opened with [`FileMapping::open_existing_readonly`].
 #[derive(Debug)]
-pub struct FileMapping {
-    _handle: Handle,
+pub enum ReadOnly {}
+
+/// Read-write access (`FILE_MAP_WRITE`).
+#[derive(Debug)]
+pub enum ReadWrite {}
+
+impl Access for ReadOnly {
+    const FILE_MAP: windows::Win32::System::Memory::FILE_MAP =
+        windows::Win32::System::Memory::FILE_MAP_READ;
+}
+
+impl Access for ReadWrite {
+    const FILE_MAP: windows::Win32::System::Memory::FILE_MAP =
+        windows::Win32::System::Memory::FILE_MAP_WRITE;
+}
+
+/// Represents a file mapping object, viewed with access `A`.
+#[derive(Debug)]
+pub struct FileMapping<A: Access = ReadWrite> {
+    // Declared first, so that the view is unmapped before the handle closes.
+    view: View<A>,
+    handle: Handle,
+}
+
+/// A view of a file mapping object, unmapped on drop.
+#[derive(Debug)]
+struct View<A: Access> {
     // SAFETY:
-    // - Must have the same lifetime as `handle`.
+    // - Must be the base address of a view mapped by `MapViewOfFile`.
     // - Must point to a valid memory region of size `size`.
-    // - The memory must be valid for reads and writes.
+    // - The memory must be valid for reads, and also for writes if `A` is `ReadWrite`.
     ptr: NonNull<u8>,
     // SAFETY:
     // - Must not exceed the actual size of the mapping.
     // - Must not exceed `isize::MAX`.
     size: usize,
-    _marker: PhantomData<*mut u8>,
+    _marker: PhantomData<(*mut u8, A)>,
 }
 
+// SAFETY: the view isn't tied to the thread that mapped it, and `FileMapping` only hands out
+// access to it through `&self`/`&mut self`.
+unsafe impl<A: Access> Send for FileMapping<A> {}
+
 impl FileMapping {
+    /// Checks whether a file mapping object named `name` exists, without mapping it.
+    pub(crate) fn exists(name: &str) -> Result<bool, ExistsError> {
+        use windows::Win32::System::Memory::{FILE_MAP_READ, OpenFileMappingW};
+
+        let name_wide = name
+            .to_u16cstring()
+            .context(exists_error::InvalidNameSnafu { name })?;
+
+        // SAFETY: opening a memory mapping object is always safe.
+        let result = unsafe {
+            OpenFileMappingW(FILE_MAP_READ.0, false, PCWSTR::from_raw(name_wide.as_ptr()))
+        };
+
+        check_exists(name, result)
+    }
+}
+
+impl<A: Access> FileMapping<A> {
+    /// Creates a new file mapping object of `size` bytes, backed by the paging file and
+    /// initially zeroed. Fails if one named `name` already exists.
+    pub fn create_new(name: &str, size: usize) -> Result<Self, CreateFileMappingError> {
+        use windows::Win32::Foundation::{
+            ERROR_ALREADY_EXISTS, GetLastError, INVALID_HANDLE_VALUE,
+        };
+        use windows::Win32::System::Memory::{CreateFileMappingW, PAGE_READWRITE};
+
+        assert!(size > 0, "`size` must not be zero");
+        assert!(
+            size <= isize::MAX as usize,
+            "`size` must not exceed `isize::MAX`"
+        );
+
+        let name_wide =
+            name.to_u16cstring()
+                .with_context(|_| create_file_mapping_error::InvalidNameSnafu {
+                    name: name.to_owned(),
+                })?;
+
+        let size_u64 = size as u64;
+
+        // SAFETY:
+        // - Creating a mapping backed by the paging file is always safe.
+        // - `name_wide` is a nul-terminated UTF-16 string.
+        let raw_handle = unsafe {
+            CreateFileMappingW(
+                INVALID_HANDLE_VALUE,
+                None,
+                PAGE_READWRITE,
+                (size_u64 >> 32) as u32,
+                size_u64 as u32,
+                PCWSTR::from_raw(name_wide.as_ptr()),
+            )
+        }
+        .with_context(|_| create_file_mapping_error::CreateSnafu {
+            name: name.to_owned(),
+        })?;
+
+        // SAFETY: reading the calling thread's last error code is always safe.
+        let already_exists = unsafe { GetLastError() } == ERROR_ALREADY_EXISTS;
+
+        // SAFETY: `CreateFileMappingW` always returns a valid handle on success.
+        let handle = unsafe { Handle::new(raw_handle) };
+
+        ensure!(
+            !already_exists,
+            create_file_mapping_error::AlreadyExistsSnafu { name }
+        );
+
+        // SAFETY:
+        // - `handle` refers to a valid mapping object.
+        // - The mapping object was just created with size `size`.
+        unsafe { Self::from_handle(handle, size) }.context(create_file_mapping_error::MapSnafu)
+    }
+
     /// Opens an existing file mapping object.
     ///
     /// # Safety
     /// - `size` must not exceed the actual size of the mapping object.
     pub unsafe fn open_existing(name: &str, size: usize) -> Result<Self, OpenFileMappingError> {
-        use windows::Win32::System::Memory::{FILE_MAP_WRITE, OpenFileMappingW};
+        use windows::Win32::System::Memory::OpenFileMappingW;
 
         assert!(size > 0, "`size` must not be zero");
         assert!(
@@ -311,16 +668,11 @@ impl FileMapping {
                 })?;
 
         // SAFETY: opening a memory mapping object is always safe.
-        let raw_handle = unsafe {
-            OpenFileMappingW(
-                FILE_MAP_WRITE.0,
-                false,
-                PCWSTR::from_raw(name_wide.as_ptr()),
-            )
-        }
-        .with_context(|_| open_file_mapping_error::OpenSnafu {
-            name: name.to_owned(),
-        })?;
+        let raw_handle =
+            unsafe { OpenFileMappingW(A::FILE_MAP.0, false, PCWSTR::from_raw(name_wide.as_ptr())) }
+                .with_context(|_| open_file_mapping_error::OpenSnafu {
+                    name: name.to_owned(),
+                })?;
 
         // SAFETY: `OpenFileMappingW` always returns a valid handle on success.
         let handle = unsafe { Handle::new(raw_handle) };
@@ -328,37 +680,162 @@ impl FileMapping {
         // SAFETY:
         // - `handle` refers to a valid mapping object.
         // - `size` doesn't exceed the size of the mapping object.
-        unsafe { Self::from_handle(handle, size) }
+        unsafe { Self::from_handle(handle, size) }.context(open_file_mapping_error::MapSnafu)
     }
 
     /// Creates a `FileMapping` from an existing file mapping object handle.
     ///
     /// # Safety
     /// - `size` must not exceed the actual size of the mapping object.
-    unsafe fn from_handle(handle: Handle, size: usize) -> Result<Self, OpenFileMappingError> {
-        use windows::Win32::System::Memory::{FILE_MAP_WRITE, MapViewOfFile};
+    unsafe fn from_handle(handle: Handle, size: usize) -> Result<Self, Win32Error> {
+        use windows::Win32::System::Memory::MapViewOfFile;
 
         // SAFETY: simply creating a new memory mapping is always safe.
-        let ptr = unsafe { MapViewOfFile(handle.0, FILE_MAP_WRITE, 0, 0, 0) }.Value;
+        let ptr = unsafe { MapViewOfFile(handle.0, A::FILE_MAP, 0, 0, 0) }.Value;
 
         let ptr = NonNull::new(ptr)
-            .ok_or_else(Win32Error::from_thread)
-            .context(open_file_mapping_error::MapSnafu)?
+            .ok_or_else(Win32Error::from_thread)?
             .cast();
 
         // SAFETY:
-        // - `handle` refers to a valid file mapping object.
-        // - `ptr` has the same lifetime as `handle`.
+        // - `ptr` was just returned by `MapViewOfFile`.
         // - `ptr` points to a region of size `size`.
-        // - `FILE_MAP_WRITE` ensures that we have read-write access.
+        // - `A::FILE_MAP` ensures that we have the access `A` promises.
         // - `size` doesn't exceed the size of the mapping object.
         // - `size` doesn't exceed `isize::MAX`.
-        Ok(FileMapping {
-            _handle: handle,
+        let view = View {
             ptr,
             size,
             _marker: PhantomData,
-        })
+        };
+
+        Ok(FileMapping { view, handle })
+    }
+
+    /// Unmaps the view and closes the mapping, reporting a failure to close it instead of just
+    /// logging it like dropping does.
+    pub fn close(self) -> Result<(), CloseError> {
+        let FileMapping { view, handle } = self;
+        drop(view);
+        handle.close().context(CloseSnafu)
+    }
+}
+
+impl FileMapping<ReadOnly> {
+    /// Opens an existing file mapping object for reading only, which works where write access
+    /// is denied.
+    ///
+    /// # Safety
+    /// - `size` must not exceed the actual size of the mapping object.
+    pub unsafe fn open_existing_readonly(
+        name: &str,
+        size: usize,
+    ) -> Result<Self, OpenFileMappingError> {
+        // SAFETY: `size` doesn't exceed the size of the mapping object.
+        unsafe { Self::open_existing(name, size) }
+    }
+}
+
+impl<A: Access> Drop for View<A> {
+    fn drop(&mut self) {
+        use windows::Win32::System::Memory::{MEMORY_MAPPED_VIEW_ADDRESS, UnmapViewOfFile};
+
+        let address = MEMORY_MAPPED_VIEW_ADDRESS {
+            Value: self.ptr.as_ptr().cast(),
+        };
+
+        // SAFETY: `ptr` is the base address of a mapped view.
+        unsafe { UnmapViewOfFile(address) }
+            .unwrap_or_else(|e| eprintln!("Failed to unmap the file mapping view: {e}."));
+    }
+}
+
+#[derive(Debug, Snafu)]
+#[snafu(module)]
+pub enum OpenPipeError {
+    #[snafu(display("invalid pipe name: `{name}`"))]
+    InvalidName {
+        source: ToUC16StringError,
+        name: String,
+    },
+
+    #[snafu(display("failed to open an existing pipe (`{name}`)"))]
+    Os { source: Win32Error, name: String },
+}
+
+#[derive(Debug, Snafu)]
+#[snafu(display("failed to write to the pipe"))]
+pub struct WritePipeError {
+    source: Win32Error,
+}
+
+/// Represents the client end of a named pipe.
+#[derive(Debug)]
+pub struct Pipe {
+    handle: Handle,
+}
+
+impl Pipe {
+    /// Opens an existing named pipe for writing.
+    pub fn open_existing(name: &str) -> Result<Self, OpenPipeError> {
+        use windows::Win32::Foundation::GENERIC_WRITE;
+        use windows::Win32::Storage::FileSystem::{
+            CreateFileW, FILE_ATTRIBUTE_NORMAL, FILE_SHARE_NONE, OPEN_EXISTING,
+        };
+
+        let name_wide =
+            name.to_u16cstring()
+                .with_context(|_| open_pipe_error::InvalidNameSnafu {
+                    name: name.to_owned(),
+                })?;
+
+        // SAFETY:
+        // - Opening an existing pipe is always safe.
+        // - `name_wide` is a nul-terminated UTF-16 string.
+        let raw_handle = unsafe {
+            CreateFileW(
+                PCWSTR::from_raw(name_wide.as_ptr()),
+                GENERIC_WRITE.0,
+                FILE_SHARE_NONE,
+                None,
+                OPEN_EXISTING,
+                FILE_ATTRIBUTE_NORMAL,
+                None,
+            )
+        }
+        .with_context(|_| open_pipe_error::OsSnafu {
+            name: name.to_owned(),
+        })?;
+
+        // SAFETY: `CreateFileW` always returns a valid handle on success.
+        let handle = unsafe { Handle::new(raw_handle) };
+
+        Ok(Pipe { handle })
+    }
+
+    /// Writes all of `bytes` to the pipe, blocking the current thread until done.
+    pub fn write_all(&mut self, mut bytes: &[u8]) -> Result<(), WritePipeError> {
+        use windows::Win32::Storage::FileSystem::WriteFile;
+
+        while !bytes.is_empty() {
+            let chunk = &bytes[..bytes.len().min(u32::MAX as usize)];
+            let mut written = 0;
+
+            // SAFETY:
+            // - `self.handle` refers to a valid pipe.
+            // - `chunk` is valid for reads of `chunk.len()` bytes.
+            unsafe { WriteFile(self.handle.0, Some(chunk), Some(&mut written), None) }
+                .context(WritePipeSnafu)?;
+
+            bytes = &bytes[written as usize..];
+        }
+
+        Ok(())
+    }
+
+    /// Closes the pipe, reporting a failure instead of just logging it like dropping does.
+    pub fn close(self) -> Result<(), CloseError> {
+        self.handle.close().context(CloseSnafu)
     }
 }
 
@@ -373,20 +850,23 @@ impl<T> Lock<T> {
         Self { mutex, value }
     }
 
-    pub fn with_lock<F, B>(&mut self, f: F) -> Result<B, LockMutexError>
+    /// Gives `f` exclusive access to the value, along with whether the mutex was abandoned
+    /// (see [`Mutex::with_lock_timeout`]).
+    pub fn with_lock<F, B>(&mut self, timeout: Duration, f: F) -> Result<B, LockMutexError>
     where
-        F: FnOnce(&mut T) -> B,
+        F: FnOnce(&mut T, bool) -> B,
     {
-        self.mutex.with_lock(|| f(&mut self.value))
+        self.mutex
+            .with_lock_timeout(timeout, |abandoned| f(&mut self.value, abandoned))
     }
 }
 
 #[derive(Debug)]
-pub struct SharedMemory {
-    mapping: Lock<FileMapping>,
+pub struct SharedMemory<A: Access = ReadWrite> {
+    mapping: Lock<FileMapping<A>>,
 }
 
-impl SharedMemory {
+impl<A: Access> SharedMemory<A> {
     /// Creates a new `SharedMemory` instance.
     ///
     /// # Safety
@@ -394,37 +874,85 @@ impl SharedMemory {
     ///   only via `mapping` by this thread.
     /// - All threads and processes must access that file mapping object only while
     ///   holding (owning) the mutex referred to by `mutex`.
-    pub unsafe fn new(mapping: FileMapping, mutex: Mutex) -> Self {
+    pub unsafe fn new(mapping: FileMapping<A>, mutex: Mutex) -> Self {
         Self {
             mapping: Lock::new(mapping, mutex),
         }
     }
 
-    pub fn with<F, B>(&mut self, f: F) -> Result<B, LockMutexError>
+    /// Gives `f` shared access to the shared memory, waiting at most `timeout` for the lock.
+    ///
+    /// `f` is also told whether the mutex was abandoned, in which case the memory may have been
+    /// left half-written.
+    pub fn with_ref<F, B>(&mut self, timeout: Duration, f: F) -> Result<B, LockMutexError>
+    where
+        F: FnOnce(&[u8], bool) -> B,
+    {
+        self.mapping.with_lock(timeout, |mapping, abandoned| {
+            // SAFETY:
+            // - Holding the lock, nobody writes to the shared memory region.
+            // - This memory is "foreign", so initialization doesn't matter.
+            // - `ptr` points to memory region of at least `size` bytes.
+            // - `size_of::<u8>() * size` doesn't exceed `isize::MAX`.
+            let slice =
+                unsafe { slice::from_raw_parts(mapping.view.ptr.as_ptr(), mapping.view.size) };
+            f(slice, abandoned)
+        })
+    }
+
+    /// Unmaps the shared memory and closes the mapping and the mutex, reporting the first
+    /// failure to close one of them.
+    pub fn close(self) -> Result<(), CloseError> {
+        let Lock { mutex, value } = self.mapping;
+        let closed_mapping = value.close();
+        closed_mapping.and(mutex.close())
+    }
+}
+
+impl SharedMemory<ReadWrite> {
+    /// Gives `f` exclusive access to the shared memory, waiting at most `timeout` for the lock.
+    ///
+    /// `f` is also told whether the mutex was abandoned, in which case the memory may have been
+    /// left half-written.
+    pub fn with<F, B>(&mut self, timeout: Duration, f: F) -> Result<B, LockMutexError>
     where
-        F: FnOnce(&mut [u8]) -> B,
+        F: FnOnce(&mut [u8], bool) -> B,
     {
-        self.mapping.with_lock(|mapping| {
+        self.mapping.with_lock(timeout, |mapping, abandoned| {
             // SAFETY:
             // - We have exclusive read-write access to the shared memory region.
             // - This memory is "foreign", so initialization doesn't matter.
             // - `ptr` points to memory region of at least `size` bytes.
             // - `size_of::<u8>() * size` doesn't exceed `isize::MAX`.
-            let slice = unsafe { slice::from_raw_parts_mut(mapping.ptr.as_ptr(), mapping.size) };
-            f(slice)
+            let slice =
+                unsafe { slice::from_raw_parts_mut(mapping.view.ptr.as_ptr(), mapping.view.size) };
+            f(slice, abandoned)
         })
     }
 }
 
-unsafe fn wait_for_single_object(handle: HANDLE) -> WaitSingle {
+const INFINITE: u32 = windows::Win32::System::Threading::INFINITE;
+
+/// Converts `timeout` to milliseconds, saturating to `INFINITE`.
+fn timeout_to_millis(timeout: Duration) -> u32 {
+    u32::try_from(timeout.as_millis()).unwrap_or(INFINITE)
+}
+
+/// Waits on `handle` for up to `timeout`, truncated to whole milliseconds. Timeouts of
+/// `u32::MAX` milliseconds or more wait indefinitely.
+///
+/// # Safety
+/// - `handle` must be a valid handle to a waitable object.
+unsafe fn wait_for_single_object(handle: HANDLE, timeout: Duration) -> WaitSingle {
     const WAIT_OBJECT_0: u32 = windows::Win32::Foundation::WAIT_OBJECT_0.0;
     const WAIT_TIMEOUT: u32 = windows::Win32::Foundation::WAIT_TIMEOUT.0;
     const WAIT_ABANDONED: u32 = windows::Win32::Foundation::WAIT_ABANDONED.0;
     const WAIT_FAILED: u32 = windows::Win32::Foundation::WAIT_FAILED.0;
 
-    use windows::Win32::System::Threading::{INFINITE, WaitForSingleObject};
+    use windows::Win32::System::Threading::WaitForSingleObject;
 
-    let result = unsafe { WaitForSingleObject(handle, INFINITE) }.0;
+    // SAFETY: `handle` is a valid handle to a waitable object.
+    let result = unsafe { WaitForSingleObject(handle, timeout_to_millis(timeout)) }.0;
 
     match result {
         WAIT_OBJECT_0 => WaitSingle::Object0,
@@ -442,3 +970,298 @@ enum WaitSingle {
     Abandoned,
     Failed(Win32Error),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns an object name no other test (or process) uses.
+    fn unique_name(name: &str) -> String {
+        format!("node-vcam-test-{}-{name}", std::process::id())
+    }
+
+    #[test]
+    fn opening_a_missing_mutex_fails_with_not_found() {
+        let result = Mutex::open_existing(&unique_name("missing-mutex"));
+        assert!(matches!(result, Err(OpenMutexError::NotFound { .. })));
+    }
+
+    #[test]
+    fn created_mutex_can_be_opened_and_locked() {
+        let name = unique_name("round-trip-mutex");
+        let created = Mutex::create_new(&name).unwrap();
+        let mut opened = Mutex::open_existing(&name).unwrap();
+
+        assert!(Mutex::exists(&name).unwrap());
+        opened.with_lock_timeout(Duration::MAX, |_| ()).unwrap();
+
+        drop((created, opened));
+        assert!(!Mutex::exists(&name).unwrap());
+    }
+
+    #[test]
+    fn locking_a_mutex_held_by_another_thread_times_out() {
+        use std::sync::mpsc;
+        use std::time::Instant;
+
+        let name = unique_name("held-mutex");
+        let mut mutex = Mutex::create_new(&name).unwrap();
+        let (locked_tx, locked_rx) = mpsc::channel();
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+
+        // A thread can always lock a mutex it owns already, so another one has to hold it.
+        let holder = std::thread::spawn({
+            let name = name.clone();
+
+            move || {
+                let mut mutex = Mutex::open_existing(&name).unwrap();
+
+                mutex
+                    .with_lock_timeout(Duration::MAX, |_| {
+                        locked_tx.send(()).unwrap();
+                        release_rx.recv().unwrap();
+                    })
+                    .unwrap();
+            }
+        });
+
+        locked_rx.recv().unwrap();
+        let start = Instant::now();
+
+        assert!(matches!(
+            mutex.with_lock_timeout(Duration::from_millis(50), |_| ()),
+            Err(LockMutexError::Timeout)
+        ));
+
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        release_tx.send(()).unwrap();
+        holder.join().unwrap();
+
+        // Released normally, so it isn't abandoned.
+        assert!(
+            !mutex
+                .with_lock_timeout(Duration::MAX, |abandoned| abandoned)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn locking_an_abandoned_mutex_reports_it_once() {
+        let name = unique_name("abandoned-mutex");
+        let mut mutex = Mutex::create_new(&name).unwrap();
+
+        let thread_name = name.clone();
+        std::thread::spawn(move || {
+            let mut mutex = Mutex::open_existing(&thread_name).unwrap();
+            // The thread exits still owning the mutex, which abandons it.
+            std::mem::forget(mutex.lock(Duration::MAX).unwrap());
+        })
+        .join()
+        .unwrap();
+
+        assert!(
+            mutex
+                .with_lock_timeout(Duration::MAX, |abandoned| abandoned)
+                .unwrap()
+        );
+        assert!(
+            !mutex
+                .with_lock_timeout(Duration::MAX, |abandoned| abandoned)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn creating_an_existing_mutex_opens_it() {
+        let name = unique_name("existing-mutex");
+        let _first = Mutex::create_new(&name).unwrap();
+        let mut second = Mutex::create_new(&name).unwrap();
+
+        second.with_lock_timeout(Duration::MAX, |_| ()).unwrap();
+    }
+
+    #[test]
+    fn created_file_mapping_can_be_opened() {
+        const SIZE: usize = 4096;
+
+        let mutex_name = unique_name("round-trip-mapping-mutex");
+        let name = unique_name("round-trip-mapping");
+        let mutex = Mutex::create_new(&mutex_name).unwrap();
+        let mapping = FileMapping::<ReadWrite>::create_new(&name, SIZE).unwrap();
+
+        // SAFETY: both views are only accessed while holding the mutex.
+        let mut writer = unsafe { SharedMemory::new(mapping, mutex) };
+
+        writer
+            .with(Duration::MAX, |bytes, _| {
+                assert!(bytes.iter().all(|&b| b == 0));
+                bytes[..4].copy_from_slice(b"vcam");
+            })
+            .unwrap();
+
+        assert!(FileMapping::exists(&name).unwrap());
+
+        // SAFETY: the mapping was created with `SIZE` bytes.
+        let mapping = unsafe { FileMapping::<ReadWrite>::open_existing(&name, SIZE) }.unwrap();
+        let mutex = Mutex::open_existing(&mutex_name).unwrap();
+
+        // SAFETY: both views are only accessed while holding the mutex.
+        let mut reader = unsafe { SharedMemory::new(mapping, mutex) };
+        let read = reader
+            .with(Duration::MAX, |bytes, _| bytes[..4].to_vec())
+            .unwrap();
+
+        assert_eq!(read, b"vcam");
+    }
+
+    #[test]
+    fn creating_an_existing_file_mapping_fails() {
+        let name = unique_name("existing-mapping");
+        let _first = FileMapping::<ReadWrite>::create_new(&name, 4096).unwrap();
+
+        assert!(matches!(
+            FileMapping::<ReadWrite>::create_new(&name, 4096),
+            Err(CreateFileMappingError::AlreadyExists { .. })
+        ));
+    }
+
+    /// Returns the error `Open*` functions fail with on `code`.
+    fn open_failure(code: u32) -> windows::core::Result<HANDLE> {
+        Err(Win32Error::from_hresult(HRESULT::from_win32(code)))
+    }
+
+    #[test]
+    fn win32_error_codes_are_found_anywhere_in_the_chain() {
+        use windows::Win32::Foundation::ERROR_ACCESS_DENIED;
+
+        let os = open_failure(ERROR_ACCESS_DENIED.0).unwrap_err();
+        let error = OpenMutexError::Os {
+            source: os,
+            name: "mutex".to_owned(),
+        };
+
+        assert_eq!(win32_error_code(&error), Some(ERROR_ACCESS_DENIED.0));
+
+        let error = OpenMutexError::NotFound {
+            name: "mutex".to_owned(),
+        };
+
+        assert_eq!(win32_error_code(&error), None);
+    }
+
+    #[test]
+    fn existence_checks_tell_missing_from_denied() {
+        use windows::Win32::Foundation::{ERROR_ACCESS_DENIED, ERROR_FILE_NOT_FOUND};
+
+        assert!(!check_exists("missing", open_failure(ERROR_FILE_NOT_FOUND.0)).unwrap());
+        assert!(matches!(
+            check_exists("denied", open_failure(ERROR_ACCESS_DENIED.0)),
+            Err(ExistsError::AccessDenied { name }) if name == "denied"
+        ));
+    }
+
+    #[test]
+    fn missing_objects_dont_exist() {
+        assert!(!Mutex::exists(&unique_name("missing-mutex")).unwrap());
+        assert!(!Event::exists(&unique_name("missing-event")).unwrap());
+        assert!(!FileMapping::exists(&unique_name("missing-mapping")).unwrap());
+    }
+
+    #[test]
+    fn objects_of_another_type_are_os_errors() {
+        let name = unique_name("event-not-mutex");
+        let (_event, _) = Event::create_new(&name).unwrap();
+
+        assert!(Event::exists(&name).unwrap());
+        assert!(matches!(Mutex::exists(&name), Err(ExistsError::Os { .. })));
+    }
+
+    #[test]
+    fn closed_event_is_gone() {
+        let name = unique_name("closed-event");
+        let (event, _) = Event::create_new(&name).unwrap();
+
+        event.close().unwrap();
+        assert!(!Event::exists(&name).unwrap());
+    }
+
+    #[test]
+    fn waiting_on_an_unsignaled_event_times_out() {
+        let (event, _) = Event::create_new(&unique_name("unsignaled-event")).unwrap();
+        let start = std::time::Instant::now();
+
+        assert!(matches!(
+            event.wait_timeout(Duration::from_millis(50)),
+            Err(WaitEventError::Timeout)
+        ));
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        // Timing out doesn't consume a later signal.
+        event.set().unwrap();
+        event.wait_timeout(Duration::ZERO).unwrap();
+    }
+
+    #[test]
+    fn creating_an_existing_event_opens_it() {
+        let name = unique_name("existing-event");
+        let (first, existed) = Event::create_new(&name).unwrap();
+        assert!(!existed);
+
+        let (second, existed) = Event::create_new(&name).unwrap();
+        assert!(existed);
+
+        // Both handles refer to the same object.
+        second.set().unwrap();
+        let waitable = Event::open_existing_waitable(&name).unwrap();
+        assert!(waitable.wait_timeout(Duration::ZERO).is_ok());
+        drop(first);
+    }
+
+    #[test]
+    fn closed_shared_memory_is_gone() {
+        let mutex_name = unique_name("closed-shared-mutex");
+        let name = unique_name("closed-shared-mapping");
+        let mutex = Mutex::create_new(&mutex_name).unwrap();
+        let mapping = FileMapping::<ReadWrite>::create_new(&name, 4096).unwrap();
+
+        // SAFETY: the mapping is only accessed while holding the mutex.
+        let shared = unsafe { SharedMemory::new(mapping, mutex) };
+
+        shared.close().unwrap();
+        assert!(!FileMapping::exists(&name).unwrap());
+        assert!(!Mutex::exists(&mutex_name).unwrap());
+    }
+
+    #[test]
+    fn timeouts_convert_to_whole_milliseconds() {
+        assert_eq!(timeout_to_millis(Duration::ZERO), 0);
+        assert_eq!(timeout_to_millis(Duration::from_millis(1500)), 1500);
+        assert_eq!(timeout_to_millis(Duration::from_micros(1500)), 1);
+        // Sub-millisecond timeouts don't wait at all.
+        assert_eq!(timeout_to_millis(Duration::from_micros(999)), 0);
+    }
+
+    #[test]
+    fn long_timeouts_clamp_to_infinite() {
+        let max_finite = u64::from(u32::MAX - 1);
+
+        assert_eq!(
+            timeout_to_millis(Duration::from_millis(max_finite)),
+            u32::MAX - 1
+        );
+        assert_eq!(
+            timeout_to_millis(Duration::from_millis(max_finite) + Duration::from_micros(999)),
+            u32::MAX - 1
+        );
+        assert_eq!(
+            timeout_to_millis(Duration::from_millis(u32::MAX.into())),
+            INFINITE
+        );
+        assert_eq!(
+            timeout_to_millis(Duration::from_millis(u64::from(u32::MAX) + 1)),
+            INFINITE
+        );
+        assert_eq!(timeout_to_millis(Duration::MAX), INFINITE);
+    }
+}